@@ -1,11 +1,12 @@
 use cwextab::*;
+use std::collections::BTreeMap;
 use std::env;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 
-fn test_decode(data: &[u8], funcs: Vec<String>) {
+fn test_decode(data: &[u8], symbols: BTreeMap<u32, String>, map: Option<&MapFile>) {
     let result = decode_extab(data);
-    let data: ExceptionTableData = match result {
+    let mut data: ExceptionTableData = match result {
         Ok(val) => val,
         Err(e) => {
             panic!(
@@ -15,20 +16,17 @@ fn test_decode(data: &[u8], funcs: Vec<String>) {
         }
     };
 
-    //Convert the table struct to a string and print it.
-    let result = data.to_string(funcs);
-    let text: String = match result {
-        Some(val) => val,
-        None => {
-            panic!("Something went wrong with converting to text :<");
-        }
-    };
+    //Name the dtors and type infos from the map file first, then by their offset in the table
+    if let Some(map) = map {
+        map.resolve_relocations(&mut data);
+    }
 
-    println!("{}", text);
+    //Convert the table struct to text and print it.
+    println!("{}", data.render(ByOffset(symbols)));
 }
 
 fn read_all_lines_from_file(path: &str) -> Vec<String> {
-    let file = File::open(path).expect(&format!("Failed to open file \"{}\"", path));
+    let file = File::open(path).unwrap_or_else(|_| panic!("Failed to open file \"{}\"", path));
     let reader = BufReader::new(file);
     reader.lines().map(|line| line.expect("Could not parse line")).collect()
 }
@@ -37,8 +35,14 @@ fn main() {
     let args: Vec<String> = env::args().collect();
 
     if args.len() > 1 {
+        let map = args.get(2).map(|path| {
+            let text = std::fs::read_to_string(path)
+                .unwrap_or_else(|_| panic!("Failed to open map file \"{}\"", path));
+            MapFile::parse(&text).unwrap_or_else(|e| panic!("Failed to parse map file: {}", e))
+        });
+
         let mut table_bytes: Vec<u8> = vec![];
-        let mut func_names: Vec<String> = vec![];
+        let mut symbols: BTreeMap<u32, String> = BTreeMap::new();
 
         let lines = read_all_lines_from_file(&args[1]);
 
@@ -46,16 +50,25 @@ fn main() {
 
         //Parse the table in the given text file
         for line in lines {
-            let cur_line: String = line;
-            let parts: Vec<&str> = cur_line.trim().split(' ').collect();
+            //Skip comments, like the ones written by emit_extab_asm
+            let cur_line: String = match line.find('#') {
+                Some(index) => line[..index].to_string(),
+                None => line,
+            };
+            if cur_line.trim().is_empty() {
+                continue;
+            }
+            let parts: Vec<&str> = cur_line.split_whitespace().collect();
 
             let data_size: u32 =
             if parts[0].starts_with(".4byte") {
                 4
             } else if parts[0].starts_with(".2byte") {
                 2
+            } else if parts[0].starts_with(".byte") {
+                1
             } else {
-                println!("Error: Invalid line in table, must start with .4byte");
+                println!("Error: Invalid line in table, must start with .4byte, .2byte or .byte");
                 return;
             };
 
@@ -67,7 +80,7 @@ fn main() {
             if let Some(hex_string) = value.strip_prefix("0x") {
                 line_val = u32::from_str_radix(hex_string, 16).expect("Failed to parse hex value");
             } else {
-                //Otherwise, treat as a function name
+                //Otherwise, treat as a symbol name for the field at this offset
                 let length: usize = value.len();
                 let func_name: String = if value.starts_with('"') && value.ends_with('"') {
                     value[1..length - 1].to_string()
@@ -75,21 +88,23 @@ fn main() {
                     value.to_string()
                 };
 
-                func_names.push(func_name);
+                symbols.insert(table_bytes.len() as u32, func_name);
             }
 
             let bytes: &[u8] =
             if data_size == 4 {
                 &line_val.to_be_bytes()
-            } else {
+            } else if data_size == 2 {
                 let u16_val: u16 = line_val as u16;
                 &u16_val.to_be_bytes()
+            } else {
+                &[line_val as u8]
             };
             table_bytes.extend_from_slice(bytes);
         }
 
-        test_decode(&table_bytes, func_names);
+        test_decode(&table_bytes, symbols, map.as_ref());
     } else {
-        println!("Usage: cwextab-bin <file>");
+        println!("Usage: cwextab-bin <file> [map file]");
     }
 }
@@ -0,0 +1,269 @@
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+
+use crate::mem_utils;
+use crate::view::ExceptionActionRef;
+use crate::SymbolResolver;
+use crate::{encode_extab_relocatable, ExAction, ExceptionTableData, ExtabEncodeError};
+
+/// Converts the table into assembler source that assembles back to the same bytes.
+///
+/// The table is written as `.4byte`/`.2byte`/`.byte` directives, grouped by PC range and by
+/// action, with a comment naming each field. Dtor and type info fields are written as
+/// references to the symbols of their relocations, named with the given resolver, and as
+/// hex values if no name is found. Only the directives are written, so the label of the
+/// table has to be added by the caller.
+///
+/// Returns an error if the table can't be encoded.
+pub fn emit_extab_asm<R: SymbolResolver>(
+    extab_data: &ExceptionTableData,
+    symbols: R,
+) -> Result<String, ExtabEncodeError> {
+    let encoded = encode_extab_relocatable(extab_data)?;
+    let data = encoded.data.as_slice();
+    let endian = extab_data.options.endian;
+
+    //Name each relocated field by its offset in the table
+    let names = extab_data.relocation_names(&symbols);
+    let mut references: BTreeMap<u32, String> = BTreeMap::new();
+    for (relocation, name) in extab_data.relocations.iter().zip(names) {
+        if let Some(name) = name {
+            references.insert(
+                relocation.offset,
+                symbol_reference(&name, relocation.addend),
+            );
+        }
+    }
+
+    let mut sb = String::new();
+    let mut offset: usize = 0;
+    let read = |offset: &mut usize, size: usize| -> u32 {
+        match size {
+            1 => mem_utils::read_byte(data, offset, true, "").unwrap_or_default() as u32,
+            2 => mem_utils::read_uint16(data, offset, true, "", endian).unwrap_or_default() as u32,
+            _ => mem_utils::read_uint32(data, offset, true, "", endian).unwrap_or_default(),
+        }
+    };
+
+    sb += "\t# Header\n";
+    sb += &directive(2, read(&mut offset, 2), "flags");
+    sb += &directive(2, read(&mut offset, 2), "et_field");
+
+    for (i, pcaction) in extab_data.pc_actions.iter().enumerate() {
+        sb += &format!(
+            "\t# PC range {i}: 0x{:X}-0x{:X}, action 0x{:X}\n",
+            pcaction.start_pc, pcaction.end_pc, pcaction.action_offset
+        );
+        sb += &directive(4, read(&mut offset, 4), "start_pc");
+        sb += &directive(2, read(&mut offset, 2), "range_size");
+        sb += &directive(2, read(&mut offset, 2), "action_offset");
+    }
+    sb += "\t# Terminator\n";
+    sb += &directive(4, read(&mut offset, 4), "terminator");
+
+    while offset < data.len() {
+        //Read the action ahead to find its fields, then write each one
+        let action = match ExceptionActionRef::read(data, &mut offset.clone(), extab_data.options) {
+            Ok(action) => action,
+            Err(_) => break,
+        };
+        sb += &format!(
+            "\t# Action 0x{:X}: {}{}\n",
+            action.action_offset,
+            action.action_type.convert_to_string(),
+            if action.has_end_bit { ", end" } else { "" }
+        );
+        sb += &directive(1, read(&mut offset, 1), "action_type");
+        sb += &directive(1, read(&mut offset, 1), "action_param");
+
+        let spec_count = match action.action_type {
+            ExAction::Specification => {
+                mem_utils::read_uint16(action.bytes, &mut 0, false, "specs", endian)
+                    .unwrap_or_default()
+            }
+            _ => 0,
+        };
+        for (field, size) in action.action_type.field_layout(spec_count) {
+            match references.get(&(offset as u32)) {
+                Some(reference) if size == 4 => {
+                    sb += &format!("\t.4byte {reference} # {field}\n");
+                    offset += 4;
+                }
+                _ => sb += &directive(size, read(&mut offset, size), field),
+            }
+        }
+    }
+    Ok(sb)
+}
+
+fn directive(size: usize, value: u32, field: &str) -> String {
+    match size {
+        1 => format!("\t.byte 0x{value:02X} # {field}\n"),
+        2 => format!("\t.2byte 0x{value:04X} # {field}\n"),
+        _ => format!("\t.4byte 0x{value:08X} # {field}\n"),
+    }
+}
+
+/// Returns the expression for a symbol with an addend, quoting names that the assembler
+/// would not accept as is, like `@123` or names with template arguments.
+fn symbol_reference(name: &str, addend: i32) -> String {
+    let plain = name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '$');
+    let mut reference = if plain && !name.starts_with(|c: char| c.is_ascii_digit()) {
+        String::from(name)
+    } else {
+        format!("\"{name}\"")
+    };
+    if addend > 0 {
+        reference += &format!("+0x{addend:X}");
+    } else if addend < 0 {
+        reference += &format!("-0x{:X}", addend.unsigned_abs());
+    }
+    reference
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use crate::{
+        encode_extab, DecodeOptions, Endian, ExtabBuilder, ExtabFlags, Location, NoSymbols, Target,
+    };
+
+    fn table(endian: Endian) -> ExceptionTableData {
+        let mut builder = ExtabBuilder::with_options(DecodeOptions {
+            endian,
+            target: Target::PowerPc,
+        });
+        let mut flags = ExtabFlags::default();
+        flags.set_has_frame_pointer(true);
+        flags.set_gpr_save_count(2).unwrap();
+        let first = builder.new_label();
+        let second = builder.new_label();
+        let tail = builder.new_label();
+        builder
+            .frame(flags)
+            .pc_range(0x10, 0x20, first)
+            .pc_range(0x40, 0x8, second);
+        builder
+            .begin_chain(first)
+            .destroy_local(0x8, 0x80001000)
+            .branch_to(tail)
+            .begin_chain(second)
+            .delete_pointer(Location::Register(31), 0x80001010)
+            .bind(tail)
+            .catch_block(0x80002000, 0x40, 0x18)
+            .specification(0x50, 0x20, &[0x80002000, 0x80002010]);
+        builder.build().unwrap()
+    }
+
+    /// Assembles the directives, with symbol references looked up in `symbols`.
+    fn assemble(asm: &str, symbols: &BTreeMap<&str, u32>, endian: Endian) -> Vec<u8> {
+        let mut data = vec![];
+        for line in asm.lines() {
+            let line = line.split('#').next().unwrap().trim();
+            let (directive, operand) = match line.split_once(' ') {
+                Some(parts) => parts,
+                None => continue,
+            };
+            let value = match operand.strip_prefix("0x") {
+                Some(hex) => u32::from_str_radix(hex, 16).unwrap(),
+                None => {
+                    let (name, addend) = match operand.split_once('+') {
+                        Some((name, addend)) => (name, addend),
+                        None => (operand, "0x0"),
+                    };
+                    let addend = u32::from_str_radix(&addend[2..], 16).unwrap();
+                    symbols[name.trim_matches('"')] + addend
+                }
+            };
+            let bytes = match endian {
+                Endian::Big => value.to_be_bytes(),
+                Endian::Little => value.to_le_bytes(),
+            };
+            match (directive, endian) {
+                (".byte", _) => data.push(value as u8),
+                (".2byte", Endian::Big) => data.extend_from_slice(&bytes[2..]),
+                (".2byte", Endian::Little) => data.extend_from_slice(&bytes[..2]),
+                (".4byte", _) => data.extend_from_slice(&bytes),
+                _ => panic!("Unexpected directive {directive}"),
+            }
+        }
+        data
+    }
+
+    #[test]
+    fn reassembles_table() {
+        let mut names: BTreeMap<u32, &str> = BTreeMap::new();
+        names.insert(0x80001000, "__dt__3FooFv");
+        names.insert(0x80002000, "@123");
+        let symbols: BTreeMap<&str, u32> = names.iter().map(|(&a, &n)| (n, a)).collect();
+
+        for endian in [Endian::Big, Endian::Little] {
+            let table = table(endian);
+            let asm = emit_extab_asm(&table, &names).unwrap();
+            assert_eq!(
+                assemble(&asm, &symbols, endian),
+                encode_extab(&table).unwrap()
+            );
+            assert!(asm.contains("\t.4byte __dt__3FooFv # dtor_address\n"));
+            assert!(asm.contains("\t.4byte \"@123\" # catch_type\n"));
+            //Unnamed fields keep their address
+            assert!(asm.contains("\t.4byte 0x80001010 # dtor_address\n"));
+            assert!(asm.contains("\t.4byte 0x80002010 # spec\n"));
+        }
+    }
+
+    #[test]
+    fn writes_symbol_references() {
+        let mut table = table(Endian::Big);
+        table.relocations[0].symbol = Some(String::from("__dt__3FooFv"));
+        table.relocations[1].symbol = Some(String::from("__dt__Q23Foo3BarFv"));
+        table.relocations[1].addend = 8;
+        table.relocations[2].symbol = Some(String::from("@123"));
+        table.relocations[3].symbol = Some(String::from("__RTTI__5Vec<f>"));
+        table.relocations[3].addend = -4;
+        let asm = emit_extab_asm(&table, NoSymbols).unwrap();
+        for (name, addend) in [
+            ("__dt__3FooFv", 0),
+            ("__dt__Q23Foo3BarFv", 8),
+            ("@123", 0),
+            ("__RTTI__5Vec<f>", -4),
+        ] {
+            let reference = symbol_reference(name, addend);
+            assert!(
+                asm.contains(&format!("\t.4byte {reference} #")),
+                "{reference}"
+            );
+        }
+        assert_eq!(symbol_reference("__dt__3FooFv", 0), "__dt__3FooFv");
+        assert_eq!(
+            symbol_reference("__dt__Q23Foo3BarFv", 8),
+            "__dt__Q23Foo3BarFv+0x8"
+        );
+        assert_eq!(symbol_reference("@123", 0), "\"@123\"");
+        assert_eq!(
+            symbol_reference("__RTTI__5Vec<f>", -4),
+            "\"__RTTI__5Vec<f>\"-0x4"
+        );
+        assert_eq!(symbol_reference("1abc", 0), "\"1abc\"");
+    }
+
+    #[test]
+    fn rejects_invalid_tables() {
+        let mut table = table(Endian::Big);
+        table.pc_actions[0].end_pc = table.pc_actions[0].start_pc + 2;
+        assert_eq!(
+            emit_extab_asm(&table, NoSymbols),
+            Err(ExtabEncodeError::InvalidPcRange {
+                index: 0,
+                start_pc: 0x10,
+                end_pc: 0x12,
+            })
+        );
+    }
+}
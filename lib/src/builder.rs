@@ -0,0 +1,788 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use thiserror::Error;
+
+use crate::mem_utils;
+use crate::Location;
+use crate::PCAction;
+use crate::{DecodeOptions, ExAction, ExceptionAction, ExceptionTableData, ExtabFlags, FrameBase};
+use alloc::string::String;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ExtabBuildError {
+    #[error("Label {0} is used but never bound to an action")]
+    UnboundLabel(u32),
+    #[error("Label {0} is bound more than once")]
+    LabelBoundTwice(u32),
+    #[error("Label {0} was not created by this builder")]
+    UnknownLabel(u32),
+    #[error("Actions must be added after a chain is started with begin_chain")]
+    ActionOutsideChain,
+    #[error("The chain starting at label {0} has no actions")]
+    EmptyChain(u32),
+    #[error("Action offset 0x{0:X} doesn't fit in 16 bits")]
+    ActionOffsetTooLarge(u32),
+    #[error("Frame offset 0x{0:X} doesn't fit in 16 bits")]
+    FrameOffsetTooLarge(u32),
+    #[error("Frame offset is relative to {base}, but the frame flags make offsets relative to {expected}")]
+    FrameBaseMismatch {
+        base: FrameBase,
+        expected: FrameBase,
+    },
+    #[error("A specification can list at most 65535 types, but {0} were given")]
+    TooManySpecTypes(usize),
+    #[error("No relocated field left for symbol \"{0}\", the last action has no more dtor or type references")]
+    NoFieldForSymbol(String),
+}
+
+/// Handle for an action position in a table being built, used as the target of PC ranges
+/// and branches.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ActionLabel(u32);
+
+#[derive(Clone, Copy)]
+enum LabelState {
+    Unbound,
+    Bound(usize), //Index of the action the label points to
+    BoundTwice,
+}
+
+struct PendingAction {
+    action_type: ExAction,
+    action_param: u8,
+    bytes: Vec<u8>,
+    branch_target: Option<ActionLabel>, //Filled in with the label offset when building
+    end_of_chain: bool,
+    symbols: Vec<String>, //Symbol names for the relocated fields, in order
+}
+
+struct PendingPCAction {
+    start_pc: u32,
+    end_pc: u32,
+    label: ActionLabel,
+}
+
+/// Builder for exception tables.
+///
+/// Actions are grouped in chains, each started with `begin_chain`. When the table is built,
+/// the actions are laid out in the order they were added, label offsets are resolved, the
+/// last action of each chain gets the end bit (unless it branches to another chain), and
+/// dtor relocations are recorded.
+///
+/// Frame offset locations are written as offsets only, and are relative to the frame
+/// pointer or stack pointer depending on the frame flags, so their base has to match the
+/// flags of the table.
+pub struct ExtabBuilder {
+    options: DecodeOptions,
+    flags: ExtabFlags,
+    pc_actions: Vec<PendingPCAction>,
+    actions: Vec<PendingAction>,
+    labels: Vec<LabelState>,
+    chain_labels: Vec<(ActionLabel, usize)>, //Chain start labels, with the index of the first action
+    stray_symbol: Option<String>,            //Symbol given before any action was added
+    deferred_error: Option<ExtabBuildError>, //First invalid value given to an action
+    frame_bases: Vec<FrameBase>, //Bases of the frame offsets given, checked when building
+}
+
+impl ExtabBuilder {
+    pub fn new() -> Self {
+        Self::with_options(DecodeOptions::default())
+    }
+
+    /// Creates a builder for a table with the given byte order and target.
+    pub fn with_options(options: DecodeOptions) -> Self {
+        Self {
+            options,
+            flags: ExtabFlags::default(),
+            pc_actions: vec![],
+            actions: vec![],
+            labels: vec![],
+            chain_labels: vec![],
+            stray_symbol: None,
+            deferred_error: None,
+            frame_bases: vec![],
+        }
+    }
+
+    /// Sets the header flags of the table.
+    pub fn frame(&mut self, flags: ExtabFlags) -> &mut Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Creates a new label, to be bound with `begin_chain` or `bind`.
+    pub fn new_label(&mut self) -> ActionLabel {
+        self.labels.push(LabelState::Unbound);
+        ActionLabel((self.labels.len() - 1) as u32)
+    }
+
+    /// Adds a PC range of `length` bytes starting at `start_pc`, whose actions start at the
+    /// given label.
+    pub fn pc_range(&mut self, start_pc: u32, length: u32, label: ActionLabel) -> &mut Self {
+        self.pc_actions.push(PendingPCAction {
+            start_pc,
+            end_pc: start_pc.wrapping_add(length),
+            label,
+        });
+        self
+    }
+
+    /// Ends the current chain, if any, and starts a new one at the given label.
+    pub fn begin_chain(&mut self, label: ActionLabel) -> &mut Self {
+        self.end_chain();
+        self.chain_labels.push((label, self.actions.len()));
+        self.bind(label)
+    }
+
+    /// Binds the label to the next action added, without ending the current chain. This
+    /// lets other chains branch into the middle of this one to share its tail.
+    pub fn bind(&mut self, label: ActionLabel) -> &mut Self {
+        //Errors are reported when building, so the builder calls can be chained
+        if let Some(state) = self.labels.get_mut(label.0 as usize) {
+            *state = match state {
+                LabelState::Unbound => LabelState::Bound(self.actions.len()),
+                _ => LabelState::BoundTwice,
+            };
+        }
+        self
+    }
+
+    fn end_chain(&mut self) {
+        if let Some(last) = self.actions.last_mut() {
+            last.end_of_chain = true;
+        }
+    }
+
+    fn push_action(
+        &mut self,
+        action_type: ExAction,
+        action_param: u8,
+        bytes: Vec<u8>,
+        branch_target: Option<ActionLabel>,
+    ) -> &mut Self {
+        self.actions.push(PendingAction {
+            action_type,
+            action_param,
+            bytes,
+            branch_target,
+            end_of_chain: false,
+            symbols: vec![],
+        });
+        self
+    }
+
+    /// Names the target symbol of the next unnamed dtor or type info reference of the last
+    /// action added. Named references are emitted as symbolic relocations when the table is
+    /// encoded with `encode_extab_relocatable`.
+    pub fn symbol(&mut self, name: &str) -> &mut Self {
+        match self.actions.last_mut() {
+            Some(action) => action.symbols.push(String::from(name)),
+            None => self.stray_symbol = Some(String::from(name)),
+        }
+        self
+    }
+
+    /// Keeps the first error found while adding actions, to be returned when building.
+    fn defer_error(&mut self, error: ExtabBuildError) {
+        self.deferred_error.get_or_insert(error);
+    }
+
+    /// Returns the value to store for a location, and whether it is a register.
+    fn location_value(&mut self, location: Location) -> (u16, bool) {
+        match location {
+            Location::FrameOffset { base, offset } => {
+                if !self.frame_bases.contains(&base) {
+                    self.frame_bases.push(base);
+                }
+                match u16::try_from(offset) {
+                    Ok(offset) => (offset, false),
+                    Err(_) => {
+                        self.defer_error(ExtabBuildError::FrameOffsetTooLarge(offset));
+                        (0, false)
+                    }
+                }
+            }
+            Location::Register(reg) => (reg, true),
+        }
+    }
+
+    /// Adds a branch to the given label. A branch ends the chain without an end bit.
+    pub fn branch_to(&mut self, label: ActionLabel) -> &mut Self {
+        //The target offset is written when building
+        self.push_action(ExAction::Branch, 0, vec![0, 0], Some(label));
+        self.end_chain();
+        self
+    }
+
+    pub fn destroy_local(&mut self, local_offset: u16, dtor_address: u32) -> &mut Self {
+        let mut bytes = vec![];
+        mem_utils::write_uint16(&mut bytes, local_offset, self.options.endian);
+        mem_utils::write_uint32(&mut bytes, dtor_address, self.options.endian);
+        self.push_action(ExAction::DestroyLocal, 0, bytes, None)
+    }
+
+    pub fn destroy_local_cond(
+        &mut self,
+        condition: Location,
+        local_offset: u16,
+        dtor_address: u32,
+    ) -> &mut Self {
+        let (condition, cond_reg) = self.location_value(condition);
+        let mut bytes = vec![];
+        mem_utils::write_uint16(&mut bytes, condition, self.options.endian);
+        mem_utils::write_uint16(&mut bytes, local_offset, self.options.endian);
+        mem_utils::write_uint16(&mut bytes, 0, self.options.endian); //unk4
+        mem_utils::write_uint32(&mut bytes, dtor_address, self.options.endian);
+        self.push_action(ExAction::DestroyLocalCond, cond_reg as u8, bytes, None)
+    }
+
+    pub fn destroy_local_pointer(&mut self, pointer: Location, dtor_address: u32) -> &mut Self {
+        let (pointer, pointer_reg) = self.location_value(pointer);
+        let mut bytes = vec![];
+        mem_utils::write_uint16(&mut bytes, pointer, self.options.endian);
+        mem_utils::write_uint32(&mut bytes, dtor_address, self.options.endian);
+        let param = (pointer_reg as u8) << 7;
+        self.push_action(ExAction::DestroyLocalPointer, param, bytes, None)
+    }
+
+    pub fn destroy_local_array(
+        &mut self,
+        local_array: u16,
+        elements: u16,
+        element_size: u16,
+        dtor_address: u32,
+    ) -> &mut Self {
+        let mut bytes = vec![];
+        mem_utils::write_uint16(&mut bytes, local_array, self.options.endian);
+        mem_utils::write_uint16(&mut bytes, elements, self.options.endian);
+        mem_utils::write_uint16(&mut bytes, element_size, self.options.endian);
+        mem_utils::write_uint32(&mut bytes, dtor_address, self.options.endian);
+        self.push_action(ExAction::DestroyLocalArray, 0, bytes, None)
+    }
+
+    fn member_action(
+        &mut self,
+        action_type: ExAction,
+        object: Location,
+        member_offset: u32,
+        dtor_address: u32,
+    ) -> &mut Self {
+        let (object, object_reg) = self.location_value(object);
+        let mut bytes = vec![];
+        mem_utils::write_uint16(&mut bytes, object, self.options.endian);
+        mem_utils::write_uint32(&mut bytes, member_offset, self.options.endian);
+        mem_utils::write_uint32(&mut bytes, dtor_address, self.options.endian);
+        self.push_action(action_type, (object_reg as u8) << 7, bytes, None)
+    }
+
+    pub fn destroy_base(
+        &mut self,
+        object: Location,
+        member_offset: u32,
+        dtor_address: u32,
+    ) -> &mut Self {
+        self.member_action(ExAction::DestroyBase, object, member_offset, dtor_address)
+    }
+
+    pub fn destroy_member(
+        &mut self,
+        object: Location,
+        member_offset: u32,
+        dtor_address: u32,
+    ) -> &mut Self {
+        self.member_action(ExAction::DestroyMember, object, member_offset, dtor_address)
+    }
+
+    pub fn destroy_member_cond(
+        &mut self,
+        condition: Location,
+        object: Location,
+        member_offset: u32,
+        dtor_address: u32,
+    ) -> &mut Self {
+        let (condition, cond_reg) = self.location_value(condition);
+        let (object, object_reg) = self.location_value(object);
+        let mut bytes = vec![];
+        mem_utils::write_uint16(&mut bytes, condition, self.options.endian);
+        mem_utils::write_uint16(&mut bytes, object, self.options.endian);
+        mem_utils::write_uint32(&mut bytes, member_offset, self.options.endian);
+        mem_utils::write_uint16(&mut bytes, 0, self.options.endian); //unk8
+        mem_utils::write_uint32(&mut bytes, dtor_address, self.options.endian);
+        let param = ((cond_reg as u8) << 7) | ((object_reg as u8) << 6);
+        self.push_action(ExAction::DestroyMemberCond, param, bytes, None)
+    }
+
+    pub fn destroy_member_array(
+        &mut self,
+        object: Location,
+        member_offset: u32,
+        elements: u32,
+        element_size: u32,
+        dtor_address: u32,
+    ) -> &mut Self {
+        let (object, object_reg) = self.location_value(object);
+        let mut bytes = vec![];
+        mem_utils::write_uint16(&mut bytes, object, self.options.endian);
+        mem_utils::write_uint32(&mut bytes, member_offset, self.options.endian);
+        mem_utils::write_uint32(&mut bytes, elements, self.options.endian);
+        mem_utils::write_uint32(&mut bytes, element_size, self.options.endian);
+        mem_utils::write_uint32(&mut bytes, dtor_address, self.options.endian);
+        let param = (object_reg as u8) << 7;
+        self.push_action(ExAction::DestroyMemberArray, param, bytes, None)
+    }
+
+    pub fn delete_pointer(&mut self, pointer: Location, dtor_address: u32) -> &mut Self {
+        let (pointer, pointer_reg) = self.location_value(pointer);
+        let mut bytes = vec![];
+        mem_utils::write_uint16(&mut bytes, pointer, self.options.endian);
+        mem_utils::write_uint32(&mut bytes, dtor_address, self.options.endian);
+        let param = (pointer_reg as u8) << 7;
+        self.push_action(ExAction::DeletePointer, param, bytes, None)
+    }
+
+    pub fn delete_pointer_cond(
+        &mut self,
+        condition: Location,
+        pointer: Location,
+        dtor_address: u32,
+    ) -> &mut Self {
+        let (condition, cond_reg) = self.location_value(condition);
+        let (pointer, pointer_reg) = self.location_value(pointer);
+        let mut bytes = vec![];
+        mem_utils::write_uint16(&mut bytes, condition, self.options.endian);
+        mem_utils::write_uint16(&mut bytes, pointer, self.options.endian);
+        mem_utils::write_uint16(&mut bytes, 0, self.options.endian); //unk4
+        mem_utils::write_uint32(&mut bytes, dtor_address, self.options.endian);
+        let param = ((cond_reg as u8) << 7) | ((pointer_reg as u8) << 6);
+        self.push_action(ExAction::DeletePointerCond, param, bytes, None)
+    }
+
+    /// Adds a catch block. The small form is used if the PC offset and catch info
+    /// offset fit in 16 bits, and the large form otherwise.
+    pub fn catch_block(
+        &mut self,
+        catch_type: u32,
+        catch_pc_offset: u32,
+        cinfo_ref: u32,
+    ) -> &mut Self {
+        if catch_pc_offset > u16::MAX as u32 || cinfo_ref > u16::MAX as u32 {
+            return self.catch_block32(catch_type, catch_pc_offset, cinfo_ref);
+        }
+        let mut bytes = vec![];
+        mem_utils::write_uint16(&mut bytes, 0, self.options.endian); //unk0
+        mem_utils::write_uint32(&mut bytes, catch_type, self.options.endian);
+        mem_utils::write_uint16(&mut bytes, catch_pc_offset as u16, self.options.endian);
+        mem_utils::write_uint16(&mut bytes, cinfo_ref as u16, self.options.endian);
+        self.push_action(ExAction::CatchBlock, 0, bytes, None)
+    }
+
+    /// Adds a catch block using the large form.
+    pub fn catch_block32(
+        &mut self,
+        catch_type: u32,
+        catch_pc_offset: u32,
+        cinfo_ref: u32,
+    ) -> &mut Self {
+        let mut bytes = vec![];
+        mem_utils::write_uint16(&mut bytes, 0, self.options.endian); //unk0
+        mem_utils::write_uint32(&mut bytes, catch_type, self.options.endian);
+        mem_utils::write_uint32(&mut bytes, catch_pc_offset, self.options.endian);
+        mem_utils::write_uint32(&mut bytes, cinfo_ref, self.options.endian);
+        self.push_action(ExAction::CatchBlock32, 0, bytes, None)
+    }
+
+    pub fn active_catch_block(&mut self, cinfo_ref: u16) -> &mut Self {
+        let mut bytes = vec![];
+        mem_utils::write_uint16(&mut bytes, cinfo_ref, self.options.endian);
+        self.push_action(ExAction::ActiveCatchBlock, 0, bytes, None)
+    }
+
+    pub fn terminate(&mut self) -> &mut Self {
+        self.push_action(ExAction::Terminate, 0, vec![], None)
+    }
+
+    pub fn specification(&mut self, pc_offset: u32, cinfo_ref: u32, spec: &[u32]) -> &mut Self {
+        let spec_count = match u16::try_from(spec.len()) {
+            Ok(count) => count,
+            Err(_) => {
+                //Still add the action, so symbols given for it don't go to the previous one
+                self.defer_error(ExtabBuildError::TooManySpecTypes(spec.len()));
+                0
+            }
+        };
+        let mut bytes = vec![];
+        mem_utils::write_uint16(&mut bytes, spec_count, self.options.endian);
+        mem_utils::write_uint32(&mut bytes, pc_offset, self.options.endian);
+        mem_utils::write_uint32(&mut bytes, cinfo_ref, self.options.endian);
+        for &address in spec {
+            mem_utils::write_uint32(&mut bytes, address, self.options.endian);
+        }
+        self.push_action(ExAction::Specification, 0, bytes, None)
+    }
+
+    /// Returns the offset of the action the label is bound to.
+    fn resolve_label(
+        &self,
+        label: ActionLabel,
+        action_offsets: &[u32],
+    ) -> Result<u32, ExtabBuildError> {
+        let index = match self.labels.get(label.0 as usize) {
+            Some(LabelState::Bound(index)) => *index,
+            Some(LabelState::Unbound) => return Err(ExtabBuildError::UnboundLabel(label.0)),
+            Some(LabelState::BoundTwice) => return Err(ExtabBuildError::LabelBoundTwice(label.0)),
+            None => return Err(ExtabBuildError::UnknownLabel(label.0)),
+        };
+        //A label bound after the last action has nothing to point to
+        match action_offsets.get(index) {
+            Some(&offset) => Ok(offset),
+            None => Err(ExtabBuildError::UnboundLabel(label.0)),
+        }
+    }
+
+    /// Lays out the table and returns the built table data.
+    pub fn build(&self) -> Result<ExceptionTableData, ExtabBuildError> {
+        if let Some(symbol) = &self.stray_symbol {
+            return Err(ExtabBuildError::NoFieldForSymbol(symbol.clone()));
+        }
+        if let Some(error) = &self.deferred_error {
+            return Err(error.clone());
+        }
+        //The flags can be set after the actions, so the bases are checked here
+        let expected = if self.flags.has_frame_pointer() {
+            FrameBase::Fp
+        } else {
+            FrameBase::Sp
+        };
+        if let Some(&base) = self.frame_bases.iter().find(|&&base| base != expected) {
+            return Err(ExtabBuildError::FrameBaseMismatch { base, expected });
+        }
+        if !self.actions.is_empty() && self.chain_labels.first().map(|c| c.1) != Some(0) {
+            return Err(ExtabBuildError::ActionOutsideChain);
+        }
+        for (i, &(label, start)) in self.chain_labels.iter().enumerate() {
+            let end = match self.chain_labels.get(i + 1) {
+                Some(&(_, next_start)) => next_start,
+                None => self.actions.len(),
+            };
+            if start == end {
+                return Err(ExtabBuildError::EmptyChain(label.0));
+            }
+        }
+
+        //Actions start after the header, the PC actions and the terminator
+        let mut action_offsets: Vec<u32> = vec![];
+        let mut offset = 8 + (self.pc_actions.len() as u32) * 8;
+        for action in &self.actions {
+            action_offsets.push(offset);
+            offset += 2 + action.bytes.len() as u32;
+        }
+
+        let mut extab_data = ExceptionTableData::new();
+        extab_data.flags = self.flags;
+        extab_data.options = self.options;
+
+        for pending in &self.pc_actions {
+            let action_offset = self.resolve_label(pending.label, &action_offsets)?;
+            if action_offset > u16::MAX as u32 {
+                return Err(ExtabBuildError::ActionOffsetTooLarge(action_offset));
+            }
+            extab_data.pc_actions.push(PCAction {
+                start_pc: pending.start_pc,
+                end_pc: pending.end_pc,
+                action_offset,
+            });
+        }
+
+        let last_index = self.actions.len().wrapping_sub(1);
+        for (i, pending) in self.actions.iter().enumerate() {
+            let mut exaction = ExceptionAction::new();
+            exaction.action_offset = action_offsets[i];
+            exaction.action_type = pending.action_type;
+            exaction.action_param = pending.action_param;
+            exaction.bytes = pending.bytes.clone();
+
+            if let Some(label) = pending.branch_target {
+                let target_offset = self.resolve_label(label, &action_offsets)?;
+                if target_offset > u16::MAX as u32 {
+                    return Err(ExtabBuildError::ActionOffsetTooLarge(target_offset));
+                }
+                exaction.bytes.clear();
+                mem_utils::write_uint16(
+                    &mut exaction.bytes,
+                    target_offset as u16,
+                    self.options.endian,
+                );
+            }
+
+            //The last action of every chain needs the end bit, except for branches which
+            //continue in another chain
+            let is_chain_end = pending.end_of_chain || i == last_index;
+            exaction.has_end_bit = is_chain_end && !matches!(pending.action_type, ExAction::Branch);
+
+            //The action data was built for the action type, so reading it can't fail
+            let mut relocations = exaction
+                .get_relocations_with_options(self.options)
+                .unwrap_or_default();
+            if pending.symbols.len() > relocations.len() {
+                let symbol = pending.symbols[relocations.len()].clone();
+                return Err(ExtabBuildError::NoFieldForSymbol(symbol));
+            }
+            for (relocation, symbol) in relocations.iter_mut().zip(&pending.symbols) {
+                relocation.symbol = Some(symbol.clone());
+            }
+            extab_data.relocations.extend(relocations);
+            extab_data.exception_actions.push(exaction);
+        }
+
+        Ok(extab_data)
+    }
+}
+
+impl Default for ExtabBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decode_extab, encode_extab, ExActionData, RelocationField};
+
+    fn sp(offset: u32) -> Location {
+        Location::FrameOffset {
+            base: FrameBase::Sp,
+            offset,
+        }
+    }
+
+    fn end_bits(table: &ExceptionTableData) -> Vec<bool> {
+        table
+            .exception_actions
+            .iter()
+            .map(|exaction| exaction.has_end_bit)
+            .collect()
+    }
+
+    #[test]
+    fn resolves_labels() {
+        let mut builder = ExtabBuilder::new();
+        let first = builder.new_label();
+        let second = builder.new_label();
+        let middle = builder.new_label();
+        //PC ranges can use labels before they are bound
+        builder
+            .pc_range(0x10, 0x8, second)
+            .pc_range(0x20, 0x8, middle);
+        builder
+            .begin_chain(first)
+            .destroy_local(0x8, 0x80001000)
+            .bind(middle)
+            .destroy_local(0xC, 0x80001004)
+            .begin_chain(second)
+            .terminate();
+        builder.pc_range(0x30, 0x8, first);
+        let table = builder.build().unwrap();
+
+        //Actions start after the header, three PC actions and the terminator
+        let offsets: Vec<u32> = table.pc_actions.iter().map(|a| a.action_offset).collect();
+        assert_eq!(offsets, [0x30, 0x28, 0x20]);
+        assert_eq!(table.pc_actions[0].end_pc, 0x18);
+        let decoded = decode_extab(&encode_extab(&table).unwrap()).unwrap();
+        assert_eq!(decoded.exception_actions.len(), 3);
+    }
+
+    #[test]
+    fn places_end_bits() {
+        let mut builder = ExtabBuilder::new();
+        let first = builder.new_label();
+        let second = builder.new_label();
+        let third = builder.new_label();
+        builder
+            .begin_chain(first)
+            .destroy_local(0x8, 0)
+            .destroy_local(0xC, 0)
+            .begin_chain(second)
+            .delete_pointer(Location::Register(31), 0)
+            .begin_chain(third)
+            .destroy_member(sp(0x10), 0x4, 0)
+            .active_catch_block(0x20);
+        let table = builder.build().unwrap();
+        assert_eq!(end_bits(&table), [false, true, true, false, true]);
+    }
+
+    #[test]
+    fn branches_share_tails() {
+        let mut builder = ExtabBuilder::new();
+        let first = builder.new_label();
+        let second = builder.new_label();
+        let tail = builder.new_label();
+        builder
+            .begin_chain(first)
+            .destroy_local(0x8, 0)
+            .branch_to(tail)
+            .begin_chain(second)
+            .destroy_local(0xC, 0)
+            .bind(tail)
+            .destroy_local(0x10, 0);
+        let table = builder.build().unwrap();
+
+        //The branch ends the first chain without the end bit
+        assert_eq!(end_bits(&table), [false, false, false, true]);
+        let branch = &table.exception_actions[1];
+        let tail_offset = table.exception_actions[3].action_offset;
+        assert!(matches!(
+            branch.get_exaction_data(),
+            Ok(ExActionData::Branch { target_offset }) if target_offset as u32 == tail_offset
+        ));
+    }
+
+    #[test]
+    fn symbols_name_relocations() {
+        let mut builder = ExtabBuilder::new();
+        let label = builder.new_label();
+        builder
+            .begin_chain(label)
+            .destroy_local(0x8, 0)
+            .symbol("__dt__3FooFv")
+            .catch_block(0, 0x40, 0x18)
+            .symbol("__RTTI__3Foo")
+            .specification(0x50, 0x20, &[0, 0])
+            .symbol("__RTTI__3Bar");
+        let table = builder.build().unwrap();
+
+        let names: Vec<(u32, RelocationField, Option<&str>)> = table
+            .relocations
+            .iter()
+            .map(|r| (r.offset, r.field, r.symbol.as_deref()))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                (0xC, RelocationField::Dtor, Some("__dt__3FooFv")),
+                (0x14, RelocationField::CatchType, Some("__RTTI__3Foo")),
+                (0x28, RelocationField::SpecType, Some("__RTTI__3Bar")),
+                (0x2C, RelocationField::SpecType, None),
+            ]
+        );
+        let relocatable = crate::encode_extab_relocatable(&table).unwrap();
+        assert_eq!(relocatable.relocations.len(), 3);
+    }
+
+    #[test]
+    fn rejects_symbols_without_fields() {
+        let mut builder = ExtabBuilder::new();
+        let label = builder.new_label();
+        builder.begin_chain(label).terminate().symbol("dtor");
+        assert_eq!(
+            builder.build().unwrap_err(),
+            ExtabBuildError::NoFieldForSymbol("dtor".into())
+        );
+
+        let mut builder = ExtabBuilder::new();
+        builder.symbol("stray");
+        assert_eq!(
+            builder.build().unwrap_err(),
+            ExtabBuildError::NoFieldForSymbol("stray".into())
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_labels() {
+        let mut builder = ExtabBuilder::new();
+        let label = builder.new_label();
+        let unbound = builder.new_label();
+        builder.begin_chain(label).branch_to(unbound);
+        assert_eq!(
+            builder.build().unwrap_err(),
+            ExtabBuildError::UnboundLabel(1)
+        );
+
+        let mut builder = ExtabBuilder::new();
+        let label = builder.new_label();
+        builder
+            .begin_chain(label)
+            .terminate()
+            .bind(label)
+            .terminate();
+        builder.pc_range(0x10, 0x8, label);
+        assert_eq!(
+            builder.build().unwrap_err(),
+            ExtabBuildError::LabelBoundTwice(0)
+        );
+
+        let mut builder = ExtabBuilder::new();
+        let first = builder.new_label();
+        let second = builder.new_label();
+        builder.begin_chain(first).begin_chain(second).terminate();
+        assert_eq!(builder.build().unwrap_err(), ExtabBuildError::EmptyChain(0));
+
+        let mut builder = ExtabBuilder::new();
+        builder.terminate();
+        assert_eq!(
+            builder.build().unwrap_err(),
+            ExtabBuildError::ActionOutsideChain
+        );
+    }
+
+    #[test]
+    fn rejects_values_too_large() {
+        let mut builder = ExtabBuilder::new();
+        let label = builder.new_label();
+        builder
+            .begin_chain(label)
+            .specification(0, 0, &vec![0; 0x10000]);
+        assert_eq!(
+            builder.build().unwrap_err(),
+            ExtabBuildError::TooManySpecTypes(0x10000)
+        );
+
+        let mut builder = ExtabBuilder::new();
+        let label = builder.new_label();
+        builder.begin_chain(label).delete_pointer(sp(0x10000), 0);
+        assert_eq!(
+            builder.build().unwrap_err(),
+            ExtabBuildError::FrameOffsetTooLarge(0x10000)
+        );
+    }
+
+    #[test]
+    fn frame_base_must_match_flags() {
+        let fp = Location::FrameOffset {
+            base: FrameBase::Fp,
+            offset: 0x10,
+        };
+        let mut builder = ExtabBuilder::new();
+        let label = builder.new_label();
+        builder.begin_chain(label).delete_pointer(fp, 0x80001000);
+        builder.pc_range(0x10, 0x8, label);
+        assert_eq!(
+            builder.build().unwrap_err(),
+            ExtabBuildError::FrameBaseMismatch {
+                base: FrameBase::Fp,
+                expected: FrameBase::Sp,
+            }
+        );
+
+        //The flags can be set after the actions
+        let mut flags = ExtabFlags::default();
+        flags.set_has_frame_pointer(true);
+        builder.frame(flags);
+        let table = builder.build().unwrap();
+        assert_eq!(
+            table.exception_actions[0]
+                .get_exaction_data()
+                .unwrap()
+                .object_location(0, table.frame_base()),
+            Some(fp)
+        );
+        builder.destroy_local_pointer(sp(0x8), 0x80001000);
+        assert_eq!(
+            builder.build().unwrap_err(),
+            ExtabBuildError::FrameBaseMismatch {
+                base: FrameBase::Sp,
+                expected: FrameBase::Fp,
+            }
+        );
+    }
+}
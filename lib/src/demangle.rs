@@ -0,0 +1,494 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloc::{format, vec};
+
+use crate::{CatchType, Relocation, SymbolResolver};
+
+/// Nesting level past which types are not demangled.
+const MAX_TYPE_DEPTH: u32 = 64;
+
+/// Demangles a symbol mangled by the Metrowerks C++ compiler, like `__dt__7CObjectFv` to
+/// `CObject::~CObject()`.
+///
+/// Returns 'None' if the symbol is not mangled or can't be demangled.
+pub fn demangle(symbol: &str) -> Option<String> {
+    let (special, symbol) = match symbol.strip_prefix("__") {
+        Some(rest) => (true, rest),
+        None => (false, symbol),
+    };
+    let split = find_split(symbol)?;
+    let name = &symbol[..split];
+    let mut rest = &symbol[split + 2..];
+
+    let mut class: Option<(String, String)> = None;
+    if rest.starts_with(|c: char| c.is_ascii_digit() || c == 'Q') {
+        let (full, base, next) = parse_qualified_name(rest, 0)?;
+        class = Some((full, base));
+        rest = next;
+    }
+    let mut is_const = false;
+    if let Some(next) = rest.strip_prefix('C') {
+        is_const = true;
+        rest = next;
+    }
+    let mut params: Option<String> = None;
+    let mut return_type: Option<String> = None;
+    if let Some(next) = rest.strip_prefix('F') {
+        let (args, next) = parse_function_args(next, 0)?;
+        params = Some(args);
+        rest = next;
+        //Template functions also mangle the return type after the parameters
+        if let Some(next) = rest.strip_prefix('_') {
+            let (pre, post, next) = parse_type(next, 0)?;
+            return_type = Some(format!("{pre}{post}"));
+            rest = next;
+        }
+    }
+    if !rest.is_empty() || (class.is_none() && params.is_none()) {
+        return None;
+    }
+
+    let name = if special {
+        special_name(name, class.as_ref().map(|c| c.1.as_str()))?
+    } else {
+        demangle_template_name(name, 0)?
+    };
+    let mut result = String::new();
+    if let Some(return_type) = return_type {
+        result += &return_type;
+        result.push(' ');
+    }
+    if let Some((full, _)) = &class {
+        result += full;
+        result += "::";
+    }
+    result += &name;
+    if let Some(params) = params {
+        result += &format!("({params})");
+    }
+    if is_const {
+        result += " const";
+    }
+    Some(result)
+}
+
+/// Finds the `__` separating the name from the class and signature. Names can hold `__`
+/// themselves, so the separator has to be followed by a class or a function signature.
+fn find_split(symbol: &str) -> Option<usize> {
+    let bytes = symbol.as_bytes();
+    let mut depth = 0;
+    for i in 0..bytes.len() {
+        match bytes[i] {
+            b'<' => depth += 1,
+            b'>' => depth -= 1,
+            b'_' if depth == 0 && i > 0 && bytes.get(i + 1) == Some(&b'_') => {
+                let valid = match bytes.get(i + 2) {
+                    Some(b'0'..=b'9') | Some(b'Q') | Some(b'F') => true,
+                    Some(b'C') => bytes.get(i + 3) == Some(&b'F'),
+                    _ => false,
+                };
+                if valid {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Returns the name of a special function, like constructors and operators.
+fn special_name(name: &str, class: Option<&str>) -> Option<String> {
+    let operator = match name {
+        "ct" => return class.map(String::from),
+        "dt" => return class.map(|class| format!("~{class}")),
+        "nw" => " new",
+        "nwa" => " new[]",
+        "dl" => " delete",
+        "dla" => " delete[]",
+        "pl" => "+",
+        "mi" => "-",
+        "ml" => "*",
+        "dv" => "/",
+        "md" => "%",
+        "er" => "^",
+        "ad" => "&",
+        "or" => "|",
+        "co" => "~",
+        "nt" => "!",
+        "as" => "=",
+        "lt" => "<",
+        "gt" => ">",
+        "apl" => "+=",
+        "ami" => "-=",
+        "amu" => "*=",
+        "adv" => "/=",
+        "amd" => "%=",
+        "aer" => "^=",
+        "aad" => "&=",
+        "aor" => "|=",
+        "ls" => "<<",
+        "rs" => ">>",
+        "als" => "<<=",
+        "ars" => ">>=",
+        "eq" => "==",
+        "ne" => "!=",
+        "le" => "<=",
+        "ge" => ">=",
+        "aa" => "&&",
+        "oo" => "||",
+        "pp" => "++",
+        "mm" => "--",
+        "cm" => ",",
+        "rm" => "->*",
+        "rf" => "->",
+        "cl" => "()",
+        "vc" => "[]",
+        _ => {
+            //Conversion operators hold the type they convert to
+            if let Some(conversion) = name.strip_prefix("op") {
+                let (pre, post, rest) = parse_type(conversion, 0)?;
+                if rest.is_empty() {
+                    return Some(format!("operator {pre}{post}"));
+                }
+            }
+            return Some(format!("__{name}"));
+        }
+    };
+    Some(format!("operator{operator}"))
+}
+
+/// Parses a name with its length prefix, like `7CObject`. Returns the demangled name and
+/// the name without template arguments.
+fn parse_name(symbol: &str, depth: u32) -> Option<(String, String, &str)> {
+    let digits = symbol
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(symbol.len());
+    let length: usize = symbol[..digits].parse().ok()?;
+    let rest = &symbol[digits..];
+    let name = rest.get(..length)?;
+    let base = match name.find('<') {
+        Some(index) => &name[..index],
+        None => name,
+    };
+    Some((
+        demangle_template_name(name, depth)?,
+        String::from(base),
+        &rest[length..],
+    ))
+}
+
+/// Parses a name that may be qualified by namespaces or classes, like `Q23foo3Bar`.
+fn parse_qualified_name(symbol: &str, depth: u32) -> Option<(String, String, &str)> {
+    let rest = match symbol.strip_prefix('Q') {
+        Some(rest) => rest,
+        None => return parse_name(symbol, depth),
+    };
+    let count = rest.chars().next()?.to_digit(10)?;
+    let mut rest = &rest[1..];
+    let mut names = vec![];
+    let mut base = String::new();
+    for _ in 0..count {
+        let (name, name_base, next) = parse_name(rest, depth)?;
+        names.push(name);
+        base = name_base;
+        rest = next;
+    }
+    Some((names.join("::"), base, rest))
+}
+
+/// Demangles the template arguments of a name, like `Vec<i,3>` to `Vec<int, 3>`.
+fn demangle_template_name(name: &str, depth: u32) -> Option<String> {
+    let start = match name.find('<') {
+        Some(start) => start,
+        None => return Some(String::from(name)),
+    };
+    let args = name[start + 1..].strip_suffix('>')?;
+    let mut result = String::from(&name[..start]);
+    let mut demangled: Vec<String> = vec![];
+    let mut brackets = 0;
+    let mut arg_start = 0;
+    for (i, c) in args
+        .char_indices()
+        .chain(core::iter::once((args.len(), ',')))
+    {
+        match c {
+            '<' => brackets += 1,
+            '>' => brackets -= 1,
+            ',' if brackets == 0 => {
+                demangled.push(demangle_template_arg(&args[arg_start..i], depth)?);
+                arg_start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    result += &format!("<{}>", demangled.join(", "));
+    Some(result)
+}
+
+fn demangle_template_arg(arg: &str, depth: u32) -> Option<String> {
+    //Non-type arguments are stored as is
+    let digits = arg.strip_prefix('-').unwrap_or(arg);
+    if !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit()) {
+        return Some(String::from(arg));
+    }
+    let (pre, post, rest) = parse_type(arg, depth + 1)?;
+    if !rest.is_empty() {
+        return None;
+    }
+    Some(format!("{pre}{post}"))
+}
+
+/// Demangles a type, like `PCc` to `const char*`.
+pub(crate) fn demangle_type(symbol: &str) -> Option<String> {
+    let (pre, post, rest) = parse_type(symbol, 0)?;
+    if !rest.is_empty() {
+        return None;
+    }
+    Some(format!("{pre}{post}"))
+}
+
+/// Parses the parameter types of a function, up to the end of the symbol or the `_` before
+/// the return type.
+fn parse_function_args(symbol: &str, depth: u32) -> Option<(String, &str)> {
+    let mut args: Vec<String> = vec![];
+    let mut rest = symbol;
+    while !rest.is_empty() && !rest.starts_with('_') {
+        let (pre, post, next) = parse_type(rest, depth + 1)?;
+        args.push(format!("{pre}{post}"));
+        rest = next;
+    }
+    //A single void parameter means there are no parameters
+    if args.len() == 1 && args[0] == "void" {
+        args.clear();
+    }
+    Some((args.join(", "), rest))
+}
+
+/// Parses a mangled type. The type is returned as the parts that go before and after the
+/// declarator, so pointers to functions and arrays can be built, like `void (*)(int)`.
+///
+/// `depth` is the nesting level of the type. Types nested deeper than `MAX_TYPE_DEPTH` are
+/// rejected, so that long symbols can't overflow the stack.
+fn parse_type(symbol: &str, depth: u32) -> Option<(String, String, &str)> {
+    if depth > MAX_TYPE_DEPTH {
+        return None;
+    }
+    let mut chars = symbol.chars();
+    let c = chars.next()?;
+    let rest = chars.as_str();
+    let builtin = match c {
+        'C' | 'V' => {
+            let qualifier = if c == 'C' { "const" } else { "volatile" };
+            let (pre, post, rest) = parse_type(rest, depth + 1)?;
+            //Qualifiers of pointers and references go after the type
+            let pre = if pre.ends_with('*') || pre.ends_with('&') {
+                format!("{pre} {qualifier}")
+            } else {
+                format!("{qualifier} {pre}")
+            };
+            return Some((pre, post, rest));
+        }
+        'P' | 'R' => {
+            let declarator = if c == 'P' { "*" } else { "&" };
+            let (pre, post, rest) = parse_type(rest, depth + 1)?;
+            if post.starts_with('(') || post.starts_with('[') {
+                return Some((format!("{pre} ({declarator}"), format!("){post}"), rest));
+            }
+            return Some((format!("{pre}{declarator}"), post, rest));
+        }
+        'M' => {
+            let (class, _, rest) = parse_qualified_name(rest, depth + 1)?;
+            let (pre, post, rest) = parse_type(rest, depth + 1)?;
+            if post.starts_with('(') {
+                return Some((format!("{pre} ({class}::*"), format!("){post}"), rest));
+            }
+            return Some((format!("{pre} {class}::*"), post, rest));
+        }
+        'F' => {
+            let (args, rest) = parse_function_args(rest, depth)?;
+            let rest = rest.strip_prefix('_')?;
+            let (pre, post, rest) = parse_type(rest, depth + 1)?;
+            return Some((format!("{pre}{post}"), format!("({args})"), rest));
+        }
+        'A' => {
+            let end = rest.find('_')?;
+            let size = &rest[..end];
+            if size.is_empty() || !size.chars().all(|c| c.is_ascii_digit()) {
+                return None;
+            }
+            let (pre, post, rest) = parse_type(&rest[end + 1..], depth + 1)?;
+            return Some((pre, format!("[{size}]{post}"), rest));
+        }
+        'U' | 'S' => {
+            let sign = if c == 'U' { "unsigned" } else { "signed" };
+            let (pre, post, rest) = parse_type(rest, depth + 1)?;
+            return Some((format!("{sign} {pre}"), post, rest));
+        }
+        'Q' | '0'..='9' => {
+            let (name, _, rest) = parse_qualified_name(symbol, depth)?;
+            return Some((name, String::new(), rest));
+        }
+        'v' => "void",
+        'b' => "bool",
+        'c' => "char",
+        'w' => "wchar_t",
+        's' => "short",
+        'i' => "int",
+        'l' => "long",
+        'x' => "long long",
+        'f' => "float",
+        'd' => "double",
+        'r' => "long double",
+        'e' => "...",
+        _ => return None,
+    };
+    Some((String::from(builtin), String::new(), rest))
+}
+
+/// Enum for how demangled names are shown.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DemangleMode {
+    /// Show the demangled name instead of the symbol
+    Replace,
+    /// Show the symbol followed by the demangled name in parentheses
+    Alongside,
+}
+
+impl Default for DemangleMode {
+    fn default() -> Self {
+        DemangleMode::Replace
+    }
+}
+
+/// Resolver that demangles the names found by another resolver. Names that can't be
+/// demangled are kept as is.
+#[derive(Debug, Clone)]
+pub struct Demangled<R> {
+    pub symbols: R,
+    pub mode: DemangleMode,
+}
+
+impl<R: SymbolResolver> Demangled<R> {
+    pub fn new(symbols: R, mode: DemangleMode) -> Self {
+        Self { symbols, mode }
+    }
+
+    fn demangle_name(&self, name: String) -> String {
+        match (demangle(&name), self.mode) {
+            (Some(demangled), DemangleMode::Replace) => demangled,
+            (Some(demangled), DemangleMode::Alongside) => format!("{name} ({demangled})"),
+            (None, _) => name,
+        }
+    }
+}
+
+impl<R: SymbolResolver> SymbolResolver for Demangled<R> {
+    fn resolve_address(&self, address: u32) -> Option<String> {
+        self.symbols
+            .resolve_address(address)
+            .map(|name| self.demangle_name(name))
+    }
+
+    fn resolve_relocation(&self, index: usize, relocation: &Relocation) -> Option<String> {
+        self.symbols
+            .resolve_relocation(index, relocation)
+            .map(|name| self.demangle_name(name))
+    }
+
+    fn resolve_type(&self, relocation: &Relocation) -> Option<CatchType> {
+        self.symbols.resolve_type(relocation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    fn check(symbol: &str, expected: &str) {
+        assert_eq!(demangle(symbol).as_deref(), Some(expected), "{symbol}");
+    }
+
+    #[test]
+    fn special_functions() {
+        check("__dt__7CObjectFv", "CObject::~CObject()");
+        check("__ct__7CObjectFv", "CObject::CObject()");
+        check(
+            "__ct__7CObjectFRC7CObject",
+            "CObject::CObject(const CObject&)",
+        );
+        check(
+            "__as__7CObjectFRC7CObject",
+            "CObject::operator=(const CObject&)",
+        );
+        check("__nw__FUl", "operator new(unsigned long)");
+        check("__dla__FPv", "operator delete[](void*)");
+        check(
+            "__eq__4Vec3CFRC4Vec3",
+            "Vec3::operator==(const Vec3&) const",
+        );
+        check("__vc__5ArrayFi", "Array::operator[](int)");
+        check("__opb__5CFlagCFv", "CFlag::operator bool() const");
+    }
+
+    #[test]
+    fn namespaces() {
+        check("__dt__Q23foo3BarFv", "foo::Bar::~Bar()");
+        check("Update__Q33foo3bar3BazFf", "foo::bar::Baz::Update(float)");
+        check("func__FQ23foo3Bar", "func(foo::Bar)");
+    }
+
+    #[test]
+    fn templates() {
+        check("__dt__8Vec<f,3>Fv", "Vec<float, 3>::~Vec()");
+        check(
+            "__ct__Q23std14list<i,-1,PCc>Fv",
+            "std::list<int, -1, const char*>::list()",
+        );
+        check(
+            "max<i>__FRCiRCi_RCi",
+            "const int& max<int>(const int&, const int&)",
+        );
+        check("get__12Box<6Box<i>>Fv", "Box<Box<int>>::get()");
+    }
+
+    #[test]
+    fn parameter_types() {
+        check("f__FPCcRVi", "f(const char*, volatile int&)");
+        check("f__FPFi_v", "f(void (*)(int))");
+        check("f__FPA4_f", "f(float (*)[4])");
+        check("f__FM5CTestFi_v", "f(void (CTest::*)(int))");
+        check("f__FPCPc", "f(char* const*)");
+        check(
+            "f__FScUcxe",
+            "f(signed char, unsigned char, long long, ...)",
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_symbols() {
+        assert_eq!(demangle("main"), None);
+        assert_eq!(demangle("__dt__7CObj"), None);
+        assert_eq!(demangle("f__FZ"), None);
+        assert_eq!(demangle("__dt__7CObjectFv_"), None);
+    }
+
+    #[test]
+    fn limits_nesting() {
+        //Deep nesting is rejected instead of overflowing the stack
+        assert_eq!(demangle(&format!("f__F{}i", "P".repeat(3000))), None);
+        let nested = format!("f__F{}i{}", "F".repeat(3000), "_v".repeat(3000));
+        assert_eq!(demangle(&nested), None);
+        let mut template = "i".to_string();
+        for _ in 0..1000 {
+            template = format!("{}Box<{template}>", template.len() + 5);
+        }
+        assert_eq!(demangle(&format!("f__F{template}")), None);
+
+        check(
+            &format!("f__F{}i", "P".repeat(10)),
+            &format!("f(int{})", "*".repeat(10)),
+        );
+    }
+}
@@ -0,0 +1,586 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use thiserror::Error;
+
+use crate::mem_utils;
+use crate::{decode_extab, decode_extabindex, Endian, ExceptionTableData, ExtabDecodeError};
+use crate::{ExtabFlags, ExtabIndexEntry, ExtabIndexError};
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum DolError {
+    #[error("DOL header should be 0x100 bytes long. Given file is 0x{0:X} bytes long.")]
+    HeaderTooSmall(u32),
+    #[error("DOL section {index} (0x{offset:X}, size 0x{size:X}) is outside of the file")]
+    InvalidSection { index: u32, offset: u32, size: u32 },
+    #[error("Address 0x{0:08X} is not inside any DOL section")]
+    UnmappedAddress(u32),
+    #[error("Invalid _eti_init_info record at 0x{0:08X}")]
+    InvalidInitInfo(u32),
+    #[error("Could not find _eti_init_info in the DOL")]
+    InitInfoNotFound,
+    #[error("Invalid extabindex: {0}")]
+    Index(ExtabIndexError),
+    #[error(
+        "Failed to decode the exception table of the function at 0x{function_address:08X}: {error}"
+    )]
+    Decode {
+        function_address: u32,
+        error: ExtabDecodeError,
+    },
+}
+
+/// Enum for the kinds of DOL sections.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DolSectionKind {
+    Text,
+    Data,
+}
+
+/// Struct for a section of a DOL file.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DolSection {
+    pub kind: DolSectionKind,
+    /// Index of the section among the sections of the same kind
+    pub index: u32,
+    pub file_offset: u32,
+    pub address: u32,
+    pub size: u32,
+}
+
+impl DolSection {
+    /// Returns whether the address is inside the section.
+    pub fn contains(&self, address: u32) -> bool {
+        address >= self.address && address - self.address < self.size
+    }
+
+    /// Returns the address right after the end of the section.
+    pub fn end(&self) -> u32 {
+        self.address.wrapping_add(self.size)
+    }
+}
+
+/// Struct for an `_eti_init_info` record, which registers the exception tables of a code
+/// range with the runtime.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct EtiInitInfo {
+    /// Address of the first extabindex entry
+    pub eti_start: u32,
+    /// Address right after the last extabindex entry
+    pub eti_end: u32,
+    pub code_start: u32,
+    pub code_size: u32,
+}
+
+impl EtiInitInfo {
+    /// Returns whether the record is the terminator of the array, which the runtime
+    /// detects by its zero extabindex start.
+    pub fn is_terminator(&self) -> bool {
+        self.eti_start == 0
+    }
+}
+
+/// Index entry with the result of decoding its table.
+type IndexedTable = (
+    ExtabIndexEntry,
+    Result<ExceptionTableData, ExtabDecodeError>,
+);
+
+/// Struct for a possible `_eti_init_info` array found by `Dol::scan_eti_init_info`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EtiInitInfoCandidate {
+    pub address: u32,
+    /// Records of the array, without the terminator
+    pub records: Vec<EtiInitInfo>,
+    /// Confidence that this is the real array, from 0 to 1
+    pub confidence: f32,
+}
+
+/// Parsed DOL executable.
+#[derive(Debug, Clone)]
+pub struct Dol<'a> {
+    data: &'a [u8],
+    sections: Vec<DolSection>,
+    pub bss_address: u32,
+    pub bss_size: u32,
+    pub entry_point: u32,
+}
+
+impl<'a> Dol<'a> {
+    const HEADER_SIZE: usize = 0x100;
+    const TEXT_SECTIONS: usize = 7;
+    const DATA_SECTIONS: usize = 11;
+    const SECTIONS: usize = Self::TEXT_SECTIONS + Self::DATA_SECTIONS;
+
+    /// Lowest confidence for `find_eti_init_info` to accept a candidate.
+    pub const MIN_CONFIDENCE: f32 = 0.5;
+
+    /// Parses the header of the provided DOL file. Empty sections are left out.
+    pub fn parse(data: &'a [u8]) -> Result<Self, DolError> {
+        if data.len() < Self::HEADER_SIZE {
+            return Err(DolError::HeaderTooSmall(data.len() as u32));
+        }
+        //The header is at least 0x100 bytes, so reading it can't fail
+        let read = |offset: usize| {
+            mem_utils::read_uint32(data, &mut { offset }, false, "header", Endian::Big).unwrap_or(0)
+        };
+
+        //The header holds the file offsets of all the sections, followed by the addresses
+        //and then the sizes
+        let mut sections = vec![];
+        for i in 0..Self::SECTIONS {
+            let (kind, index) = if i < Self::TEXT_SECTIONS {
+                (DolSectionKind::Text, i)
+            } else {
+                (DolSectionKind::Data, i - Self::TEXT_SECTIONS)
+            };
+            let section = DolSection {
+                kind,
+                index: index as u32,
+                file_offset: read(i * 4),
+                address: read(0x48 + i * 4),
+                size: read(0x90 + i * 4),
+            };
+            if section.size == 0 {
+                continue;
+            }
+            let end = section.file_offset as usize + section.size as usize;
+            if end > data.len() {
+                return Err(DolError::InvalidSection {
+                    index: i as u32,
+                    offset: section.file_offset,
+                    size: section.size,
+                });
+            }
+            sections.push(section);
+        }
+
+        Ok(Self {
+            data,
+            sections,
+            bss_address: read(0xD8),
+            bss_size: read(0xDC),
+            entry_point: read(0xE0),
+        })
+    }
+
+    /// Returns the text and data sections of the DOL.
+    pub fn sections(&self) -> &[DolSection] {
+        &self.sections
+    }
+
+    /// Returns the section that holds the given address, if any.
+    pub fn section_at(&self, address: u32) -> Option<&DolSection> {
+        self.sections
+            .iter()
+            .find(|section| section.contains(address))
+    }
+
+    /// Converts a virtual address to an offset in the DOL file.
+    pub fn virtual_to_file(&self, address: u32) -> Option<u32> {
+        let section = self.section_at(address)?;
+        Some(section.file_offset + (address - section.address))
+    }
+
+    /// Returns the data from the given address to the end of its section.
+    pub fn data_at(&self, address: u32) -> Result<&'a [u8], DolError> {
+        let section = self
+            .section_at(address)
+            .ok_or(DolError::UnmappedAddress(address))?;
+        let start = (section.file_offset + (address - section.address)) as usize;
+        let end = (section.file_offset + section.size) as usize;
+        Ok(&self.data[start..end])
+    }
+
+    /// Returns `size` bytes of data at the given address. The data can't cross the end of
+    /// a section.
+    pub fn read(&self, address: u32, size: u32) -> Result<&'a [u8], DolError> {
+        let data = self.data_at(address)?;
+        data.get(..size as usize).ok_or(DolError::UnmappedAddress(
+            address.wrapping_add(data.len() as u32),
+        ))
+    }
+
+    /// Reads a 32 bit value at the given address.
+    pub fn read_u32(&self, address: u32) -> Result<u32, DolError> {
+        let bytes = self.read(address, 4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    /// Reads the `_eti_init_info` records at the given address, up to the zero terminator.
+    pub fn eti_init_info(&self, address: u32) -> Result<Vec<EtiInitInfo>, DolError> {
+        let mut records = vec![];
+        let mut record_address = address;
+        loop {
+            let info = self.read_init_info_record(record_address)?;
+            if info.is_terminator() {
+                break;
+            }
+            if info.eti_end < info.eti_start
+                || (info.eti_end - info.eti_start) as usize % ExtabIndexEntry::SIZE != 0
+            {
+                return Err(DolError::InvalidInitInfo(record_address));
+            }
+            records.push(info);
+            record_address = record_address.wrapping_add(16);
+        }
+        Ok(records)
+    }
+
+    fn read_init_info_record(&self, address: u32) -> Result<EtiInitInfo, DolError> {
+        Ok(EtiInitInfo {
+            eti_start: self.read_u32(address)?,
+            eti_end: self.read_u32(address.wrapping_add(4))?,
+            code_start: self.read_u32(address.wrapping_add(8))?,
+            code_size: self.read_u32(address.wrapping_add(12))?,
+        })
+    }
+
+    /// Returns whether the record has an extabindex range inside a section and a code range
+    /// inside a text section. The extabindex can be in a text section, since the linker
+    /// places it after .init in retail DOLs.
+    fn is_plausible_record(&self, info: &EtiInitInfo) -> bool {
+        let is_index = info.eti_end > info.eti_start
+            && (info.eti_end - info.eti_start) as usize % ExtabIndexEntry::SIZE == 0
+            && self
+                .sections
+                .iter()
+                .any(|s| s.contains(info.eti_start) && info.eti_end <= s.end());
+        let is_code = info.code_size != 0
+            && self.sections.iter().any(|s| {
+                s.kind == DolSectionKind::Text
+                    && s.contains(info.code_start)
+                    && info.code_start.wrapping_add(info.code_size) <= s.end()
+            });
+        is_index && is_code
+    }
+
+    /// Scores the `_eti_init_info` array candidate at the given address. Returns `None` if
+    /// the first record isn't plausible or an extabindex range isn't sorted.
+    fn score_candidate(&self, address: u32) -> Option<EtiInitInfoCandidate> {
+        let mut records = vec![];
+        let mut terminated = false;
+        let mut record_address = address;
+        while let Ok(info) = self.read_init_info_record(record_address) {
+            if info.is_terminator() {
+                terminated = true;
+                break;
+            }
+            if !self.is_plausible_record(&info) {
+                break;
+            }
+            records.push(info);
+            record_address = record_address.wrapping_add(16);
+        }
+        if records.is_empty() {
+            return None;
+        }
+
+        let mut entries: u32 = 0;
+        let mut in_code: u32 = 0;
+        let mut decoded: u32 = 0;
+        let mut whole_sections: u32 = 0;
+        for info in &records {
+            let code_end = info.code_start.wrapping_add(info.code_size);
+            for (entry, table) in self.record_tables(info).ok()? {
+                entries += 1;
+                if entry.function_address >= info.code_start && entry.function_end() <= code_end {
+                    in_code += 1;
+                }
+                if table.is_ok() {
+                    decoded += 1;
+                }
+            }
+            //The runtime registers the whole text section of the executable
+            if self.sections.iter().any(|s| {
+                s.kind == DolSectionKind::Text
+                    && s.address == info.code_start
+                    && s.size == info.code_size
+            }) {
+                whole_sections += 1;
+            }
+        }
+
+        let ratio = |count: u32, total: u32| count as f32 / total as f32;
+        let confidence = 0.4 * ratio(decoded, entries)
+            + 0.3 * ratio(in_code, entries)
+            + 0.15 * ratio(whole_sections, records.len() as u32)
+            + if terminated { 0.15 } else { 0.0 };
+        Some(EtiInitInfoCandidate {
+            address,
+            records,
+            confidence,
+        })
+    }
+
+    /// Scans the text and data sections for possible `_eti_init_info` arrays, and returns
+    /// them sorted by decreasing confidence. Retail DOLs have the array in .init, which is
+    /// the first text section.
+    ///
+    /// A candidate is an array of 4 word records whose extabindex range is inside a section,
+    /// whose code range is inside a text section, and whose extabindex entries are sorted. The confidence depends on how many of the referenced tables decode cleanly,
+    /// how many functions are inside the code range, whether the code range is a whole text
+    /// section and whether the array has a terminator.
+    pub fn scan_eti_init_info(&self) -> Vec<EtiInitInfoCandidate> {
+        let mut candidates: Vec<EtiInitInfoCandidate> = vec![];
+        for section in &self.sections {
+            //Records are word aligned, but not necessarily aligned to their size
+            let mut address = section.address;
+            while address.wrapping_add(16) <= section.end() {
+                match self.score_candidate(address) {
+                    Some(candidate) => {
+                        //Skip the records of the array, which would be found again
+                        address += candidate.records.len() as u32 * 16;
+                        candidates.push(candidate);
+                    }
+                    None => address += 4,
+                }
+            }
+        }
+        candidates.sort_by(|a, b| {
+            b.confidence
+                .partial_cmp(&a.confidence)
+                .unwrap_or(core::cmp::Ordering::Equal)
+        });
+        candidates
+    }
+
+    /// Looks for the `_eti_init_info` array in the DOL, and returns its address.
+    ///
+    /// This returns the best candidate of `scan_eti_init_info`, if its confidence is at
+    /// least `MIN_CONFIDENCE`.
+    pub fn find_eti_init_info(&self) -> Option<u32> {
+        self.scan_eti_init_info()
+            .first()
+            .filter(|candidate| candidate.confidence >= Self::MIN_CONFIDENCE)
+            .map(|candidate| candidate.address)
+    }
+
+    /// Decodes the extabindex of an `_eti_init_info` record, and the table of each entry.
+    fn record_tables(&self, info: &EtiInitInfo) -> Result<Vec<IndexedTable>, DolError> {
+        let index_data = self.read(info.eti_start, info.eti_end - info.eti_start)?;
+        let entries = decode_extabindex(index_data).map_err(DolError::Index)?;
+
+        //Tables are stored back to back, so each table ends where the next one starts.
+        //The last table ends at the extabindex, which follows the tables.
+        let mut starts: Vec<u32> = entries
+            .iter()
+            .filter(|entry| !entry.is_direct_store())
+            .map(|entry| entry.extab_address)
+            .collect();
+        starts.push(info.eti_start);
+        starts.sort_unstable();
+        starts.dedup();
+
+        let mut tables = vec![];
+        for entry in entries {
+            let table = if entry.is_direct_store() {
+                let mut table = ExceptionTableData::new();
+                table.flags = ExtabFlags::from_bits(entry.extab_address);
+                Ok(table)
+            } else {
+                let start = entry.extab_address;
+                let mut data = self.data_at(start)?;
+                if let Some(&end) = starts.iter().find(|&&address| address > start) {
+                    data = &data[..data.len().min((end - start) as usize)];
+                }
+                decode_extab(data)
+            };
+            tables.push((entry, table));
+        }
+        Ok(tables)
+    }
+
+    /// Decodes every exception table registered by the `_eti_init_info` array at the given
+    /// address, and returns the function address, function size and table of each one.
+    pub fn decode_tables(
+        &self,
+        eti_init_info: u32,
+    ) -> Result<Vec<(u32, u32, ExceptionTableData)>, DolError> {
+        let mut tables = vec![];
+        for info in self.eti_init_info(eti_init_info)? {
+            for (entry, table) in self.record_tables(&info)? {
+                let table = table.map_err(|error| DolError::Decode {
+                    function_address: entry.function_address,
+                    error,
+                })?;
+                tables.push((entry.function_address, entry.function_size(), table));
+            }
+        }
+        Ok(tables)
+    }
+
+    /// Finds the `_eti_init_info` array with `find_eti_init_info`, and decodes every
+    /// exception table it registers. See `decode_tables`.
+    pub fn decode_all_tables(&self) -> Result<Vec<(u32, u32, ExceptionTableData)>, DolError> {
+        let address = self
+            .find_eti_init_info()
+            .ok_or(DolError::InitInfoNotFound)?;
+        self.decode_tables(address)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INIT: u32 = 0x80003100;
+    const TEXT: u32 = 0x80003200;
+    const DATA: u32 = 0x80004000;
+
+    /// Table of the function, with one PC range and a DestroyLocal action.
+    const TABLE: [u8; 24] = [
+        0x00, 0x00, 0x00, 0x00, //Header
+        0x00, 0x00, 0x00, 0x04, 0x00, 0x02, 0x00, 0x10, //PC action
+        0x00, 0x00, 0x00, 0x00, //Terminator
+        0x82, 0x00, 0x00, 0x08, 0x80, 0x00, 0x10, 0x00, //DestroyLocal
+    ];
+
+    fn write_u32(data: &mut [u8], offset: u32, value: u32) {
+        let offset = offset as usize;
+        data[offset..offset + 4].copy_from_slice(&value.to_be_bytes());
+    }
+
+    /// Builds a DOL like a retail one, with the table, the extabindex and `_eti_init_info`
+    /// in .init (text section 0), the code in text section 1, and a data section.
+    fn dol_file() -> Vec<u8> {
+        let mut data = vec![0; 0x100 + 0x80 + 0x40 + 0x40];
+        //Section offsets, addresses and sizes
+        for (i, (offset, address, size)) in [
+            (0, 0x100, INIT, 0x80),
+            (1, 0x180, TEXT, 0x40),
+            (7, 0x1C0, DATA, 0x40),
+        ]
+        .iter()
+        .map(|&(i, offset, address, size)| (i, (offset, address, size)))
+        {
+            write_u32(&mut data, i * 4, offset);
+            write_u32(&mut data, 0x48 + i * 4, address);
+            write_u32(&mut data, 0x90 + i * 4, size);
+        }
+        write_u32(&mut data, 0xE0, INIT);
+
+        //Table, index entry and init info record with its terminator
+        data[0x100..0x118].copy_from_slice(&TABLE);
+        write_u32(&mut data, 0x118, TEXT + 0x20);
+        write_u32(&mut data, 0x11C, 0x20);
+        write_u32(&mut data, 0x120, INIT);
+        write_u32(&mut data, 0x130, INIT + 0x18);
+        write_u32(&mut data, 0x134, INIT + 0x24);
+        write_u32(&mut data, 0x138, TEXT);
+        write_u32(&mut data, 0x13C, 0x40);
+
+        //Code, so that it doesn't read as a record
+        for offset in (0x180..0x1C0).step_by(4) {
+            write_u32(&mut data, offset, 0x4E800020);
+        }
+        data
+    }
+
+    #[test]
+    fn parses_sections() {
+        let data = dol_file();
+        let dol = Dol::parse(&data).unwrap();
+        assert_eq!(dol.sections().len(), 3);
+        assert_eq!(
+            dol.sections()[2],
+            DolSection {
+                kind: DolSectionKind::Data,
+                index: 0,
+                file_offset: 0x1C0,
+                address: DATA,
+                size: 0x40,
+            }
+        );
+        assert_eq!(dol.entry_point, INIT);
+        assert_eq!(dol.virtual_to_file(TEXT + 4), Some(0x184));
+        assert_eq!(dol.read_u32(TEXT), Ok(0x4E800020));
+        assert_eq!(
+            dol.read(TEXT + 0x3C, 8),
+            Err(DolError::UnmappedAddress(TEXT + 0x40))
+        );
+        assert_eq!(
+            dol.read_u32(0x80000000),
+            Err(DolError::UnmappedAddress(0x80000000))
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_files() {
+        let data = dol_file();
+        assert_eq!(
+            Dol::parse(&data[..0x80]).unwrap_err(),
+            DolError::HeaderTooSmall(0x80)
+        );
+        assert_eq!(
+            Dol::parse(&data[..0x1C0]).unwrap_err(),
+            DolError::InvalidSection {
+                index: 7,
+                offset: 0x1C0,
+                size: 0x40,
+            }
+        );
+    }
+
+    #[test]
+    fn reads_init_info() {
+        let data = dol_file();
+        let dol = Dol::parse(&data).unwrap();
+        let records = dol.eti_init_info(INIT + 0x30).unwrap();
+        assert_eq!(
+            records,
+            vec![EtiInitInfo {
+                eti_start: INIT + 0x18,
+                eti_end: INIT + 0x24,
+                code_start: TEXT,
+                code_size: 0x40,
+            }]
+        );
+        assert!(!records[0].is_terminator());
+        //The extabindex range has to hold whole entries
+        let mut data = data;
+        write_u32(&mut data, 0x134, INIT + 0x20);
+        let dol = Dol::parse(&data).unwrap();
+        assert_eq!(
+            dol.eti_init_info(INIT + 0x30),
+            Err(DolError::InvalidInitInfo(INIT + 0x30))
+        );
+    }
+
+    #[test]
+    fn finds_init_info_in_text_section() {
+        let data = dol_file();
+        let dol = Dol::parse(&data).unwrap();
+        let candidates = dol.scan_eti_init_info();
+        assert_eq!(candidates[0].address, INIT + 0x30);
+        assert_eq!(candidates[0].records.len(), 1);
+        assert!(candidates[0].confidence > 0.99);
+        assert_eq!(dol.find_eti_init_info(), Some(INIT + 0x30));
+
+        let tables = dol.decode_all_tables().unwrap();
+        assert_eq!(tables.len(), 1);
+        let (function_address, function_size, table) = &tables[0];
+        assert_eq!((*function_address, *function_size), (TEXT + 0x20, 0x20));
+        assert_eq!(table.exception_actions.len(), 1);
+        assert_eq!(table.relocations[0].address, 0x80001000);
+    }
+
+    #[test]
+    fn requires_mapped_ranges() {
+        //Code range outside of the text sections
+        let mut data = dol_file();
+        write_u32(&mut data, 0x138, DATA);
+        let dol = Dol::parse(&data).unwrap();
+        assert_eq!(dol.find_eti_init_info(), None);
+        assert_eq!(
+            dol.decode_all_tables().unwrap_err(),
+            DolError::InitInfoNotFound
+        );
+
+        //Extabindex range outside of the sections
+        let mut data = dol_file();
+        write_u32(&mut data, 0x130, 0x81000000);
+        write_u32(&mut data, 0x134, 0x8100000C);
+        let dol = Dol::parse(&data).unwrap();
+        assert_eq!(dol.find_eti_init_info(), None);
+    }
+}
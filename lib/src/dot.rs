@@ -0,0 +1,198 @@
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::render::ActionFields;
+use crate::{ExAction, ExActionData, ExceptionTableData, SymbolResolver};
+
+/// Converts the table into a Graphviz DOT graph of its PC ranges and action chains.
+///
+/// Each PC range is a node pointing at its first action. Each action is a node labelled
+/// with its offset, type, operands and dtor, named with the given resolver. Actions fall
+/// through to the next action in the table (dashed edges) until one with the end bit,
+/// and branches point at their target (bold edges). Since each action is drawn once,
+/// chains that share a tail merge into the same nodes, and actions reached from more than
+/// one place are filled in grey. Actions with the end bit have a double border, and
+/// references to offsets without an action point at a red node.
+pub fn emit_extab_dot<R: SymbolResolver>(extab_data: &ExceptionTableData, symbols: R) -> String {
+    let renderer = extab_data.render(&symbols);
+    let actions = &extab_data.exception_actions;
+    let starts: BTreeSet<u32> = actions.iter().map(|a| a.action_offset).collect();
+
+    //Collect the edges first, to find the actions that chains merge into
+    let mut edges: Vec<(String, u32, &str)> = vec![];
+    for (i, pcaction) in extab_data.pc_actions.iter().enumerate() {
+        edges.push((format!("pc{i}"), pcaction.action_offset, ""));
+    }
+    for (i, action) in actions.iter().enumerate() {
+        let from = action_node(action.action_offset);
+        if matches!(action.action_type, ExAction::Branch) {
+            if let Ok(ExActionData::Branch { target_offset }) =
+                action.get_exaction_data_with_options(extab_data.options)
+            {
+                edges.push((from, target_offset as u32, "style=bold"));
+            }
+        } else if !action.has_end_bit {
+            if let Some(next) = actions.get(i + 1) {
+                edges.push((from, next.action_offset, "style=dashed"));
+            }
+        }
+    }
+    let mut incoming: BTreeMap<u32, usize> = BTreeMap::new();
+    for (_, to, _) in &edges {
+        *incoming.entry(*to).or_default() += 1;
+    }
+
+    let mut sb = String::from("digraph extab {\n");
+    sb += "\tnode [shape=box, fontname=\"monospace\"];\n";
+
+    for (i, pcaction) in extab_data.pc_actions.iter().enumerate() {
+        let range = if pcaction.start_pc != pcaction.end_pc {
+            format!("0x{:X}-0x{:X}", pcaction.start_pc, pcaction.end_pc)
+        } else {
+            format!("0x{:X}", pcaction.start_pc)
+        };
+        sb += &format!("\tpc{i} [shape=ellipse, label=\"PC {range}\"];\n");
+    }
+
+    for action in actions {
+        let offset = action.action_offset;
+        let fields = ActionFields {
+            renderer: &renderer,
+            action,
+        };
+        let mut label = format!(
+            "{offset:06X}: {}\n{fields}",
+            ExAction::ACTION_NAMES[action.action_type.to_int() as usize]
+        );
+        //Skip the branch target line, since the edge shows it
+        if matches!(action.action_type, ExAction::Branch) {
+            label.truncate(label.find('\n').unwrap_or(label.len()) + 1);
+        }
+        let mut attributes = format!("label=\"{}\"", escape_label(&label));
+        if action.has_end_bit {
+            attributes += ", peripheries=2";
+        }
+        if incoming.get(&offset).copied().unwrap_or(0) > 1 {
+            attributes += ", style=filled, fillcolor=lightgrey";
+        }
+        sb += &format!("\t{} [{attributes}];\n", action_node(offset));
+    }
+
+    //References to offsets where no action starts
+    let missing: BTreeSet<u32> = edges
+        .iter()
+        .map(|(_, to, _)| *to)
+        .filter(|to| !starts.contains(to))
+        .collect();
+    for offset in missing {
+        sb += &format!(
+            "\t{} [label=\"{offset:06X}: no action\", color=red];\n",
+            action_node(offset)
+        );
+    }
+
+    for (from, to, style) in &edges {
+        if style.is_empty() {
+            sb += &format!("\t{from} -> {};\n", action_node(*to));
+        } else {
+            sb += &format!("\t{from} -> {} [{style}];\n", action_node(*to));
+        }
+    }
+    sb += "}\n";
+    sb
+}
+
+fn action_node(offset: u32) -> String {
+    format!("action_{offset:X}")
+}
+
+/// Escapes the label text for DOT, with each line left aligned.
+fn escape_label(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            '\\' => escaped += "\\\\",
+            '"' => escaped += "\\\"",
+            '\n' => escaped += "\\l",
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ExtabBuilder, NoSymbols};
+
+    /// Table with two chains that share a tail, the first one through a branch.
+    fn table() -> ExceptionTableData {
+        let mut builder = ExtabBuilder::new();
+        let first = builder.new_label();
+        let second = builder.new_label();
+        let tail = builder.new_label();
+        builder
+            .pc_range(0x10, 0x20, first)
+            .pc_range(0x40, 0x8, second);
+        builder
+            .begin_chain(first)
+            .destroy_local(0x8, 0x80001000)
+            .branch_to(tail)
+            .begin_chain(second)
+            .destroy_local(0xC, 0x80001004)
+            .bind(tail)
+            .destroy_local(0x10, 0x80001008);
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn draws_chains() {
+        let mut names: BTreeMap<u32, &str> = BTreeMap::new();
+        names.insert(0x80001000, "__dt__3FooFv");
+        assert_eq!(
+            emit_extab_dot(&table(), &names),
+            "digraph extab {
+	node [shape=box, fontname=\"monospace\"];
+	pc0 [shape=ellipse, label=\"PC 0x10-0x30\"];
+	pc1 [shape=ellipse, label=\"PC 0x40-0x48\"];
+	action_18 [label=\"000018: DESTROYLOCAL\\lLocal: 0x8(SP)\\lDtor: \\\"__dt__3FooFv\\\"\\l\"];
+	action_20 [label=\"000020: BRANCH\\l\"];
+	action_24 [label=\"000024: DESTROYLOCAL\\lLocal: 0xC(SP)\\lDtor: 80001004\\l\"];
+	action_2C [label=\"00002C: DESTROYLOCAL\\lLocal: 0x10(SP)\\lDtor: 80001008\\l\", peripheries=2, style=filled, fillcolor=lightgrey];
+	pc0 -> action_18;
+	pc1 -> action_24;
+	action_18 -> action_20 [style=dashed];
+	action_20 -> action_2C [style=bold];
+	action_24 -> action_2C [style=dashed];
+}
+"
+        );
+    }
+
+    #[test]
+    fn marks_missing_actions() {
+        let mut table = table();
+        table.pc_actions[1].action_offset = 0x100;
+        table.pc_actions[1].end_pc = table.pc_actions[1].start_pc;
+        let dot = emit_extab_dot(&table, NoSymbols);
+        assert!(dot.contains("\tpc1 [shape=ellipse, label=\"PC 0x40\"];\n"));
+        assert!(dot.contains("\taction_100 [label=\"000100: no action\", color=red];\n"));
+        assert!(dot.contains("\tpc1 -> action_100;\n"));
+        assert!(!dot.contains("-> action_24"));
+    }
+
+    #[test]
+    fn escapes_labels() {
+        assert_eq!(
+            escape_label("Dtor: \"a\\b\"\nPC: 10"),
+            "Dtor: \\\"a\\\\b\\\"\\lPC: 10"
+        );
+        let mut names: BTreeMap<u32, &str> = BTreeMap::new();
+        names.insert(0x80001000, "say\"hi\"\\");
+        let dot = emit_extab_dot(&table(), &names);
+        assert!(dot.contains("\\lDtor: \\\"say\\\"hi\\\"\\\\\\\"\\l\"];\n"));
+    }
+}
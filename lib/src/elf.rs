@@ -0,0 +1,516 @@
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use object::elf;
+use object::read::elf::ElfFile32;
+use object::{Architecture, Endianness, Object, ObjectKind, ObjectSection, ObjectSymbol};
+use object::{RelocationFlags, RelocationTarget, SectionIndex, SymbolKind};
+use thiserror::Error;
+
+use crate::{decode_extab, ExceptionTableData, ExtabDecodeError, MemoryImage, Relocation};
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ExtabElfError {
+    #[error("Failed to parse ELF file: {0}")]
+    Object(object::read::Error),
+    #[error("ELF file is not a PowerPC object")]
+    UnsupportedArchitecture,
+    #[error("ELF file has an .extabindex section but no .extab section")]
+    MissingExtab,
+    #[error(".extabindex size 0x{0:X} is not a multiple of 12")]
+    InvalidIndexSize(u32),
+    #[error("Relocation at offset 0x{offset:X} of {section} has an unsupported type {r_type}")]
+    UnsupportedRelocation {
+        section: &'static str,
+        offset: u32,
+        r_type: u32,
+    },
+    #[error(".extabindex entry {index} points outside of .extab (offset 0x{offset:X})")]
+    InvalidExtabOffset { index: u32, offset: u32 },
+    #[error("Failed to decode the exception table of {function}: {error}")]
+    Decode {
+        function: String,
+        error: ExtabDecodeError,
+    },
+}
+
+impl From<object::read::Error> for ExtabElfError {
+    fn from(error: object::read::Error) -> Self {
+        ExtabElfError::Object(error)
+    }
+}
+
+/// Struct for the exception table of a function in an ELF file.
+#[derive(Debug, Clone)]
+pub struct ElfExtab {
+    /// Name of the function the table belongs to
+    pub function: String,
+    /// Offset of the table in the .extab section
+    pub extab_offset: u32,
+    pub table: ExceptionTableData,
+}
+
+/// Relocation target in the ELF file, as a symbol name and addend.
+struct RelocTarget {
+    symbol: String,
+    address: u32,
+    addend: i32,
+}
+
+struct ElfExtabReader<'a> {
+    file: ElfFile32<'a, Endianness>,
+    /// Name of the first symbol defined at each address, by section index and address
+    symbols: BTreeMap<(usize, u32), String>,
+}
+
+impl<'a> ElfExtabReader<'a> {
+    fn new(file: ElfFile32<'a, Endianness>) -> Self {
+        let mut symbols = BTreeMap::new();
+        for symbol in file.symbols() {
+            let section = match symbol.section_index() {
+                Some(section) if symbol.kind() != SymbolKind::Section => section,
+                _ => continue,
+            };
+            match symbol.name() {
+                Ok(name) if !name.is_empty() => {
+                    symbols
+                        .entry((section.0, symbol.address() as u32))
+                        .or_insert_with(|| String::from(name));
+                }
+                _ => {}
+            }
+        }
+        Self { file, symbols }
+    }
+
+    /// Returns the name of the symbol, using the section name for section symbols.
+    fn symbol_name(&self, symbol: &object::read::elf::ElfSymbol32<'a, '_, Endianness>) -> String {
+        if symbol.kind() == SymbolKind::Section {
+            if let Some(section) = symbol
+                .section_index()
+                .and_then(|index| self.file.section_by_index(index).ok())
+            {
+                return String::from(section.name().unwrap_or_default());
+            }
+        }
+        String::from(symbol.name().unwrap_or_default())
+    }
+
+    /// Returns the name of the symbol defined at the given address of a section, if any.
+    /// Without a section, the sections holding the address are searched.
+    fn symbol_at(&self, section: Option<SectionIndex>, address: u32) -> Option<String> {
+        let name = match section {
+            Some(section) => self.symbols.get(&(section.0, address)),
+            None => self
+                .file
+                .sections()
+                .filter(|section| {
+                    let start = section.address();
+                    (start..start + section.size()).contains(&(address as u64))
+                })
+                .find_map(|section| self.symbols.get(&(section.index().0, address))),
+        };
+        name.cloned()
+    }
+
+    /// Returns the ADDR32 relocations of the section, by offset.
+    fn section_relocations(
+        &self,
+        section: &object::read::elf::ElfSection32<'a, '_, Endianness>,
+        section_name: &'static str,
+    ) -> Result<BTreeMap<u32, RelocTarget>, ExtabElfError> {
+        let mut relocations = BTreeMap::new();
+        for (offset, relocation) in section.relocations() {
+            let offset = offset as u32;
+            let r_type = match relocation.flags() {
+                RelocationFlags::Elf { r_type } => r_type,
+                _ => 0,
+            };
+            if r_type != elf::R_PPC_ADDR32 {
+                return Err(ExtabElfError::UnsupportedRelocation {
+                    section: section_name,
+                    offset,
+                    r_type,
+                });
+            }
+            let symbol = match relocation.target() {
+                RelocationTarget::Symbol(index) => self.file.symbol_by_index(index)?,
+                _ => continue,
+            };
+            let addend = relocation.addend() as i32;
+            let address = (symbol.address() as u32).wrapping_add(addend as u32);
+
+            //Relocations against a section symbol are resolved to the symbol at that
+            //address when there is one, like local functions referenced through .text
+            let target = match symbol.kind() {
+                SymbolKind::Section => match self.symbol_at(symbol.section_index(), address) {
+                    Some(name) => RelocTarget {
+                        symbol: name,
+                        address,
+                        addend: 0,
+                    },
+                    None => RelocTarget {
+                        symbol: self.symbol_name(&symbol),
+                        address,
+                        addend,
+                    },
+                },
+                _ => RelocTarget {
+                    symbol: self.symbol_name(&symbol),
+                    address,
+                    addend,
+                },
+            };
+            relocations.insert(offset, target);
+        }
+        Ok(relocations)
+    }
+
+    fn read_tables(&self) -> Result<Vec<ElfExtab>, ExtabElfError> {
+        if self.file.architecture() != Architecture::PowerPc {
+            return Err(ExtabElfError::UnsupportedArchitecture);
+        }
+        let index_section = match self.file.section_by_name(".extabindex") {
+            Some(section) => section,
+            None => return Ok(vec![]),
+        };
+        let extab_section = self
+            .file
+            .section_by_name(".extab")
+            .ok_or(ExtabElfError::MissingExtab)?;
+
+        let index_data = index_section.data()?;
+        let extab_data = extab_section.data()?;
+        if index_data.len() % 12 != 0 {
+            return Err(ExtabElfError::InvalidIndexSize(index_data.len() as u32));
+        }
+        let index_relocations = self.section_relocations(&index_section, ".extabindex")?;
+        let extab_relocations = self.section_relocations(&extab_section, ".extab")?;
+        let extab_section_address = extab_section.address() as u32;
+
+        //Find the function and table offset of every index entry. Relocatable objects
+        //have relocations for both, while linked files hold the final addresses.
+        let mut entries: Vec<(String, u32)> = vec![];
+        for (i, entry) in index_data.chunks_exact(12).enumerate() {
+            let entry_offset = (i * 12) as u32;
+            let function = match index_relocations.get(&entry_offset) {
+                Some(target) => target.symbol.clone(),
+                None => {
+                    let address = u32::from_be_bytes([entry[0], entry[1], entry[2], entry[3]]);
+                    self.symbol_at(None, address)
+                        .unwrap_or_else(|| format!("fn_{address:08X}"))
+                }
+            };
+            let extab_address = match index_relocations.get(&(entry_offset + 8)) {
+                Some(target) => target.address,
+                None => u32::from_be_bytes([entry[8], entry[9], entry[10], entry[11]]),
+            };
+            let extab_offset = extab_address.wrapping_sub(extab_section_address);
+            if extab_offset as usize >= extab_data.len() {
+                return Err(ExtabElfError::InvalidExtabOffset {
+                    index: i as u32,
+                    offset: extab_offset,
+                });
+            }
+            entries.push((function, extab_offset));
+        }
+
+        //Tables are stored back to back, so each one ends where the next one starts
+        let mut starts: Vec<u32> = entries.iter().map(|entry| entry.1).collect();
+        starts.sort_unstable();
+        starts.dedup();
+
+        let mut tables: Vec<ElfExtab> = vec![];
+        for (function, extab_offset) in entries {
+            let end = match starts.iter().find(|&&start| start > extab_offset) {
+                Some(&start) => start,
+                None => extab_data.len() as u32,
+            };
+            let data = &extab_data[extab_offset as usize..end as usize];
+            let mut table = decode_extab(data).map_err(|error| ExtabElfError::Decode {
+                function: function.clone(),
+                error,
+            })?;
+
+            for relocation in &mut table.relocations {
+                match extab_relocations.get(&(extab_offset + relocation.offset)) {
+                    Some(target) => {
+                        relocation.symbol = Some(target.symbol.clone());
+                        relocation.addend = target.addend;
+                    }
+                    //Without a relocation, the field holds the address in a linked file
+                    None if relocation.address != 0 => {
+                        relocation.symbol = self.symbol_at(None, relocation.address);
+                    }
+                    None => {}
+                }
+            }
+
+            tables.push(ElfExtab {
+                function,
+                extab_offset,
+                table,
+            });
+        }
+        Ok(tables)
+    }
+}
+
+/// Reads every exception table of a PowerPC ELF file.
+///
+/// The `.extab` section is split into the table of each function using the `.extabindex`
+/// entries, and the dtor and type info relocations of each table are resolved to symbol
+/// names using the `.extab` relocations. Returns an empty list if the file has no
+/// `.extabindex` section.
+pub fn read_elf_extab(data: &[u8]) -> Result<Vec<ElfExtab>, ExtabElfError> {
+    let file = ElfFile32::<Endianness>::parse(data)?;
+    ElfExtabReader::new(file).read_tables()
+}
+
+/// Section of an ELF image.
+struct ImageSection<'a> {
+    name: String,
+    address: u32,
+    data: &'a [u8],
+}
+
+/// Sections and symbols of a PowerPC ELF file, for reading the data that tables point at,
+/// like the type descriptors of catch blocks.
+///
+/// Relocations with a symbol are read from the section of the symbol, so relocatable
+/// objects are supported as well as linked files.
+pub struct ElfImage<'a> {
+    sections: Vec<ImageSection<'a>>,
+    symbols: BTreeMap<String, (usize, u32)>, //Section and offset of each symbol, by name
+    linked: bool,
+}
+
+impl<'a> ElfImage<'a> {
+    pub fn parse(data: &'a [u8]) -> Result<Self, ExtabElfError> {
+        let file = ElfFile32::<Endianness>::parse(data)?;
+        if file.architecture() != Architecture::PowerPc {
+            return Err(ExtabElfError::UnsupportedArchitecture);
+        }
+        let mut sections = vec![];
+        let mut section_indices = BTreeMap::new();
+        for section in file.sections() {
+            let data = match section.data() {
+                Ok(data) if !data.is_empty() => data,
+                _ => continue,
+            };
+            section_indices.insert(section.index().0, sections.len());
+            sections.push(ImageSection {
+                name: String::from(section.name().unwrap_or_default()),
+                address: section.address() as u32,
+                data,
+            });
+        }
+
+        let mut symbols = BTreeMap::new();
+        for symbol in file.symbols() {
+            let name = match symbol.name() {
+                Ok(name) if !name.is_empty() && symbol.kind() != SymbolKind::Section => name,
+                _ => continue,
+            };
+            let section = match symbol
+                .section_index()
+                .and_then(|index| section_indices.get(&index.0))
+            {
+                Some(&section) => section,
+                None => continue,
+            };
+            let offset = (symbol.address() as u32).wrapping_sub(sections[section].address);
+            symbols
+                .entry(String::from(name))
+                .or_insert((section, offset));
+        }
+
+        Ok(Self {
+            sections,
+            symbols,
+            linked: file.kind() != ObjectKind::Relocatable,
+        })
+    }
+}
+
+impl MemoryImage for ElfImage<'_> {
+    fn bytes_at(&self, address: u32) -> Option<&[u8]> {
+        //Sections of relocatable objects all start at 0
+        if !self.linked {
+            return None;
+        }
+        self.sections
+            .iter()
+            .find(|section| {
+                address >= section.address
+                    && ((address - section.address) as usize) < section.data.len()
+            })
+            .map(|section| &section.data[(address - section.address) as usize..])
+    }
+
+    fn relocation_bytes(&self, relocation: &Relocation) -> Option<&[u8]> {
+        let symbol = match &relocation.symbol {
+            Some(symbol) => symbol,
+            None => return self.bytes_at(relocation.address),
+        };
+        let addend = relocation.addend as u32;
+        //Section symbols are named after their section
+        let (data, offset) = match self.symbols.get(symbol) {
+            Some(&(section, offset)) => (self.sections[section].data, offset.wrapping_add(addend)),
+            None => {
+                let section = self
+                    .sections
+                    .iter()
+                    .find(|section| &section.name == symbol)?;
+                (section.data, addend)
+            }
+        };
+        data.get(offset as usize..)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{encode_extab, read_catch_type, ExtabBuilder, RelocationField};
+    use object::write::{self, SymbolSection};
+    use object::{BinaryFormat, SectionKind, SymbolFlags, SymbolScope};
+
+    fn add_symbol(
+        obj: &mut write::Object,
+        name: &str,
+        value: u64,
+        section: Option<write::SectionId>,
+    ) -> write::SymbolId {
+        obj.add_symbol(write::Symbol {
+            name: name.as_bytes().to_vec(),
+            value,
+            size: 0,
+            kind: match section {
+                Some(_) => SymbolKind::Text,
+                None => SymbolKind::Unknown,
+            },
+            scope: SymbolScope::Linkage,
+            weak: false,
+            section: match section {
+                Some(section) => SymbolSection::Section(section),
+                None => SymbolSection::Undefined,
+            },
+            flags: SymbolFlags::None,
+        })
+    }
+
+    fn add_addr32(
+        obj: &mut write::Object,
+        section: write::SectionId,
+        offset: u32,
+        symbol: write::SymbolId,
+        addend: i64,
+    ) {
+        obj.add_relocation(
+            section,
+            write::Relocation {
+                offset: offset as u64,
+                symbol,
+                addend,
+                flags: RelocationFlags::Elf {
+                    r_type: elf::R_PPC_ADDR32,
+                },
+            },
+        )
+        .unwrap();
+    }
+
+    /// Relocatable object with two functions and their tables. The second function is
+    /// local, and is referenced through the .text section symbol like CodeWarrior does.
+    fn object_file() -> Vec<u8> {
+        let mut obj = write::Object::new(BinaryFormat::Elf, Architecture::PowerPc, Endianness::Big);
+        let text = obj.add_section(vec![], b".text".to_vec(), SectionKind::Text);
+        let data = obj.add_section(vec![], b".data".to_vec(), SectionKind::Data);
+        let extab = obj.add_section(vec![], b".extab".to_vec(), SectionKind::ReadOnlyData);
+        let index = obj.add_section(vec![], b".extabindex".to_vec(), SectionKind::ReadOnlyData);
+
+        obj.append_section_data(text, &[0; 0x40], 4);
+        obj.append_section_data(data, b"!Foo!0!\0", 4);
+        let first = add_symbol(&mut obj, "first", 0x0, Some(text));
+        let second = add_symbol(&mut obj, "second", 0x20, Some(text));
+        obj.symbol_mut(second).scope = SymbolScope::Compilation;
+        let rtti = add_symbol(&mut obj, "__RTTI__3Foo", 0x0, Some(data));
+        let dtor = add_symbol(&mut obj, "__dt__3FooFv", 0, None);
+        let text_symbol = obj.section_symbol(text);
+        let extab_symbol = obj.section_symbol(extab);
+
+        let mut builder = ExtabBuilder::new();
+        let label = builder.new_label();
+        builder.begin_chain(label).destroy_local(0x8, 0);
+        builder.pc_range(0x4, 0x8, label);
+        let first_table = encode_extab(&builder.build().unwrap()).unwrap();
+        let mut builder = ExtabBuilder::new();
+        let label = builder.new_label();
+        builder.begin_chain(label).catch_block(0, 0x10, 0x8);
+        builder.pc_range(0x4, 0x8, label);
+        let second_table = encode_extab(&builder.build().unwrap()).unwrap();
+
+        let first_offset = obj.append_section_data(extab, &first_table, 4) as u32;
+        let second_offset = obj.append_section_data(extab, &second_table, 4) as u32;
+        let first_relocation = decode_extab(&first_table).unwrap().relocations[0].offset;
+        let second_relocation = decode_extab(&second_table).unwrap().relocations[0].offset;
+        add_addr32(&mut obj, extab, first_offset + first_relocation, dtor, 0);
+        add_addr32(&mut obj, extab, second_offset + second_relocation, rtti, 0);
+
+        obj.append_section_data(index, &[0; 24], 4);
+        add_addr32(&mut obj, index, 0x0, first, 0);
+        add_addr32(&mut obj, index, 0x8, extab_symbol, first_offset as i64);
+        add_addr32(&mut obj, index, 0xC, text_symbol, 0x20);
+        add_addr32(&mut obj, index, 0x14, extab_symbol, second_offset as i64);
+        obj.write().unwrap()
+    }
+
+    #[test]
+    fn reads_tables() {
+        let data = object_file();
+        let tables = read_elf_extab(&data).unwrap();
+        assert_eq!(tables.len(), 2);
+        assert_eq!(tables[0].function, "first");
+        assert_eq!(tables[0].extab_offset, 0);
+        assert_eq!(tables[0].table.exception_actions.len(), 1);
+        let relocation = &tables[0].table.relocations[0];
+        assert_eq!(relocation.field, RelocationField::Dtor);
+        assert_eq!(relocation.symbol.as_deref(), Some("__dt__3FooFv"));
+
+        //The section symbol is resolved to the local function at that address
+        assert_eq!(tables[1].function, "second");
+        assert_eq!(tables[1].extab_offset, 0x18);
+        let relocation = &tables[1].table.relocations[0];
+        assert_eq!(relocation.field, RelocationField::CatchType);
+        assert_eq!(relocation.symbol.as_deref(), Some("__RTTI__3Foo"));
+    }
+
+    #[test]
+    fn reads_type_descriptors() {
+        let data = object_file();
+        let tables = read_elf_extab(&data).unwrap();
+        let image = ElfImage::parse(&data).unwrap();
+        let relocation = &tables[1].table.relocations[0];
+        assert_eq!(
+            read_catch_type(&image, relocation).map(|t| t.type_name()),
+            Some(String::from("Foo"))
+        );
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        let obj = write::Object::new(BinaryFormat::Elf, Architecture::Arm, Endianness::Little);
+        assert_eq!(
+            read_elf_extab(&obj.write().unwrap()).unwrap_err(),
+            ExtabElfError::UnsupportedArchitecture
+        );
+        let obj = write::Object::new(BinaryFormat::Elf, Architecture::PowerPc, Endianness::Big);
+        assert!(read_elf_extab(&obj.write().unwrap()).unwrap().is_empty());
+        assert!(matches!(
+            read_elf_extab(&[0; 16]),
+            Err(ExtabElfError::Object(_))
+        ));
+    }
+}
@@ -0,0 +1,516 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use thiserror::Error;
+
+use crate::mem_utils;
+use crate::{ExAction, ExActionData, ExceptionAction, ExceptionTableData, PCAction};
+use crate::{Relocation, RelocationField};
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ExtabEncodeError {
+    #[error("PC action {index} has a start PC of zero, which would be read as the terminator")]
+    ZeroStartPc { index: u32 },
+    #[error("PC action {index} has an invalid range 0x{start_pc:08X}-0x{end_pc:08X}: the size must be a multiple of 4 below 0x40000")]
+    InvalidPcRange {
+        index: u32,
+        start_pc: u32,
+        end_pc: u32,
+    },
+    #[error(
+        "PC action {index} has an action offset 0x{action_offset:X} that doesn't fit in 16 bits"
+    )]
+    ActionOffsetTooLarge { index: u32, action_offset: u32 },
+    #[error("Exception action {index} has offset 0x{actual:X}, but it would be encoded at offset 0x{expected:X}")]
+    ActionOffsetMismatch {
+        index: u32,
+        expected: u32,
+        actual: u32,
+    },
+    #[error(
+        "Exception action {index} has {actual} bytes of data, but its action type needs {expected}"
+    )]
+    InvalidActionDataSize {
+        index: u32,
+        expected: u32,
+        actual: u32,
+    },
+    #[error("Relocation at offset 0x{offset:X} doesn't point to a dtor or type info field")]
+    InvalidRelocationOffset { offset: u32 },
+    #[error(
+        "PC action {index} points to offset 0x{action_offset:X}, where no exception action starts"
+    )]
+    UnknownActionOffset { index: u32, action_offset: u32 },
+    #[error("Exception action {index} branches to offset 0x{target_offset:X}, where no exception action starts")]
+    UnknownBranchTarget { index: u32, target_offset: u32 },
+    #[error("Relocation at offset 0x{offset:X} doesn't point to a {field:?} field")]
+    InvalidRelocationField { offset: u32, field: RelocationField },
+}
+
+struct ExtabEncoder<'a> {
+    extab_data: &'a ExceptionTableData,
+    data: Vec<u8>,
+}
+
+impl<'a> ExtabEncoder<'a> {
+    fn new(extab_data: &'a ExceptionTableData) -> Self {
+        Self {
+            extab_data,
+            data: vec![],
+        }
+    }
+
+    fn encode_exception_table(&mut self) -> Result<(), ExtabEncodeError> {
+        //Write the header
+        let flags = self.extab_data.flags;
+        mem_utils::write_uint16(
+            &mut self.data,
+            flags.flag_val(),
+            self.extab_data.options.endian,
+        );
+        mem_utils::write_uint16(
+            &mut self.data,
+            flags.et_field(),
+            self.extab_data.options.endian,
+        );
+
+        //Write the range entries, followed by the terminator
+        for (index, pcaction) in self.extab_data.pc_actions.iter().enumerate() {
+            self.encode_pc_action(index as u32, pcaction)?;
+        }
+        mem_utils::write_uint32(&mut self.data, 0, self.extab_data.options.endian);
+
+        for (index, exaction) in self.extab_data.exception_actions.iter().enumerate() {
+            self.encode_action_entry(index as u32, exaction)?;
+        }
+
+        Ok(())
+    }
+
+    fn encode_pc_action(
+        &mut self,
+        index: u32,
+        pcaction: &PCAction,
+    ) -> Result<(), ExtabEncodeError> {
+        if pcaction.start_pc == 0 {
+            return Err(ExtabEncodeError::ZeroStartPc { index });
+        }
+
+        //The range size is encoded as size >> 2
+        let range_size = pcaction.end_pc.wrapping_sub(pcaction.start_pc);
+        if range_size % 4 != 0 || range_size / 4 > u16::MAX as u32 {
+            return Err(ExtabEncodeError::InvalidPcRange {
+                index,
+                start_pc: pcaction.start_pc,
+                end_pc: pcaction.end_pc,
+            });
+        }
+        if pcaction.action_offset > u16::MAX as u32 {
+            return Err(ExtabEncodeError::ActionOffsetTooLarge {
+                index,
+                action_offset: pcaction.action_offset,
+            });
+        }
+
+        mem_utils::write_uint32(
+            &mut self.data,
+            pcaction.start_pc,
+            self.extab_data.options.endian,
+        );
+        mem_utils::write_uint16(
+            &mut self.data,
+            (range_size / 4) as u16,
+            self.extab_data.options.endian,
+        );
+        mem_utils::write_uint16(
+            &mut self.data,
+            pcaction.action_offset as u16,
+            self.extab_data.options.endian,
+        );
+        Ok(())
+    }
+
+    fn encode_action_entry(
+        &mut self,
+        index: u32,
+        exaction: &ExceptionAction,
+    ) -> Result<(), ExtabEncodeError> {
+        //Actions are stored back to back, so the offset of each action has to match
+        //where it ends up in the table.
+        let offset = self.data.len() as u32;
+        if exaction.action_offset != offset {
+            return Err(ExtabEncodeError::ActionOffsetMismatch {
+                index,
+                expected: offset,
+                actual: exaction.action_offset,
+            });
+        }
+
+        let spec_count = match exaction.action_type {
+            ExAction::Specification => mem_utils::read_uint16(
+                &exaction.bytes,
+                &mut 0,
+                false,
+                "specs",
+                self.extab_data.options.endian,
+            )
+            .unwrap_or(0),
+            _ => 0,
+        };
+        let size = exaction.action_type.data_size(spec_count);
+        if exaction.bytes.len() != size {
+            return Err(ExtabEncodeError::InvalidActionDataSize {
+                index,
+                expected: size as u32,
+                actual: exaction.bytes.len() as u32,
+            });
+        }
+
+        let mut action_type_byte = exaction.action_type.to_int() as u8;
+        if exaction.has_end_bit {
+            action_type_byte |= 0x80;
+        }
+        mem_utils::write_byte(&mut self.data, action_type_byte);
+        mem_utils::write_byte(&mut self.data, exaction.action_param);
+        self.data.extend_from_slice(&exaction.bytes);
+        Ok(())
+    }
+}
+
+/// Struct for an encoded exception table with symbolic relocations.
+#[derive(Debug, Clone)]
+pub struct RelocatableExtab {
+    /// Table data, with zeroes in the relocated fields
+    pub data: Vec<u8>,
+    /// Relocations with a target symbol, sorted by offset
+    pub relocations: Vec<Relocation>,
+}
+
+/// Encodes the provided exception table data into the bytes of an exception table.
+///
+/// The table is written in the byte order from its options. Encoding a table returned by
+/// `decode_extab` gives back the exact bytes it was decoded from.
+pub fn encode_extab(extab_data: &ExceptionTableData) -> Result<Vec<u8>, ExtabEncodeError> {
+    let mut encoder = ExtabEncoder::new(extab_data);
+    encoder.encode_exception_table()?;
+    Ok(encoder.data)
+}
+
+/// Encodes the provided exception table data for a relocatable object.
+///
+/// Every relocation in the table data with a symbol is returned as a symbolic relocation,
+/// with its field zeroed out in the table data. The symbol and addend are meant to be
+/// written as an `R_PPC_ADDR32` relocation. Fields without a symbol keep their address.
+pub fn encode_extab_relocatable(
+    extab_data: &ExceptionTableData,
+) -> Result<RelocatableExtab, ExtabEncodeError> {
+    let mut data = encode_extab(extab_data)?;
+
+    //Collect the offsets of every relocated field in the table
+    let mut field_offsets: Vec<u32> = vec![];
+    for exaction in &extab_data.exception_actions {
+        let fields = exaction
+            .action_type
+            .relocation_fields(&exaction.bytes, extab_data.options);
+        let base = exaction.action_offset + 2;
+        field_offsets.extend(fields.iter().map(|&(offset, _)| base + offset));
+    }
+
+    let mut relocations: Vec<Relocation> = vec![];
+    for relocation in &extab_data.relocations {
+        if relocation.symbol.is_none() {
+            continue;
+        }
+        if !field_offsets.contains(&relocation.offset) {
+            return Err(ExtabEncodeError::InvalidRelocationOffset {
+                offset: relocation.offset,
+            });
+        }
+        let start = relocation.offset as usize;
+        data[start..start + 4].fill(0);
+        relocations.push(relocation.clone());
+    }
+    relocations.sort_by_key(|relocation| relocation.offset);
+
+    Ok(RelocatableExtab { data, relocations })
+}
+
+/// Checks that the table can be encoded, and that its references are consistent: every PC
+/// action and branch points at the start of an exception action, and every relocation
+/// points to a field of its kind.
+///
+/// `encode_extab` doesn't make the reference checks, so that tables decoded from unusual
+/// data can still be encoded back to the same bytes.
+pub fn validate_extab(extab_data: &ExceptionTableData) -> Result<(), ExtabEncodeError> {
+    ExtabEncoder::new(extab_data).encode_exception_table()?;
+
+    let starts: Vec<u32> = extab_data
+        .exception_actions
+        .iter()
+        .map(|exaction| exaction.action_offset)
+        .collect();
+    for (index, pcaction) in extab_data.pc_actions.iter().enumerate() {
+        if !starts.contains(&pcaction.action_offset) {
+            return Err(ExtabEncodeError::UnknownActionOffset {
+                index: index as u32,
+                action_offset: pcaction.action_offset,
+            });
+        }
+    }
+
+    let mut fields: Vec<(u32, RelocationField)> = vec![];
+    for (index, exaction) in extab_data.exception_actions.iter().enumerate() {
+        //The data sizes were checked by the encoder, so the data can be decoded
+        let data = exaction.get_exaction_data_with_options(extab_data.options);
+        if let Ok(ExActionData::Branch { target_offset }) = data {
+            if !starts.contains(&(target_offset as u32)) {
+                return Err(ExtabEncodeError::UnknownBranchTarget {
+                    index: index as u32,
+                    target_offset: target_offset as u32,
+                });
+            }
+        }
+        let base = exaction.action_offset + 2;
+        let action_fields = exaction
+            .action_type
+            .relocation_fields(&exaction.bytes, extab_data.options);
+        fields.extend(
+            action_fields
+                .into_iter()
+                .map(|(offset, field)| (base + offset, field)),
+        );
+    }
+    for relocation in &extab_data.relocations {
+        if !fields.contains(&(relocation.offset, relocation.field)) {
+            return Err(ExtabEncodeError::InvalidRelocationField {
+                offset: relocation.offset,
+                field: relocation.field,
+            });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decode_extab_with_options, DecodeOptions, Endian, Target};
+
+    /// Value and size of each field of an action.
+    type Fields = &'static [(u32, usize)];
+
+    /// Every action type with its param and fields. The branch points at the catch block,
+    /// after the end bit of the first chain.
+    const ACTIONS: &[(ExAction, u8, Fields)] = &[
+        (ExAction::DestroyLocal, 0, &[(0x8, 2), (0x80001000, 4)]),
+        (
+            ExAction::DestroyLocalCond,
+            0x1,
+            &[(0x1F, 2), (0xC, 2), (0, 2), (0x80001004, 4)],
+        ),
+        (
+            ExAction::DestroyLocalPointer,
+            0x80,
+            &[(0x1E, 2), (0x80001008, 4)],
+        ),
+        (
+            ExAction::DestroyLocalArray,
+            0,
+            &[(0x20, 2), (4, 2), (0xC, 2), (0x8000100C, 4)],
+        ),
+        (
+            ExAction::DestroyBase,
+            0x80,
+            &[(0x1F, 2), (0, 4), (0x80001010, 4)],
+        ),
+        (
+            ExAction::DestroyMember,
+            0,
+            &[(0x10, 2), (0x8, 4), (0x80001014, 4)],
+        ),
+        (
+            ExAction::DestroyMemberCond,
+            0xC0,
+            &[(0x1D, 2), (0x1F, 2), (0x4, 4), (0, 2), (0x80001018, 4)],
+        ),
+        (
+            ExAction::DestroyMemberArray,
+            0,
+            &[(0x30, 2), (0x4, 4), (2, 4), (8, 4), (0x8000101C, 4)],
+        ),
+        (ExAction::DeletePointer, 0x80, &[(0x1C, 2), (0x80001020, 4)]),
+        (
+            ExAction::DeletePointerCond,
+            0x40,
+            &[(0x14, 2), (0x1B, 2), (0, 2), (0x80001024, 4)],
+        ),
+        (ExAction::Terminate, 0, &[]),
+        (ExAction::Branch, 0, &[(0x96, 2)]),
+        (
+            ExAction::CatchBlock,
+            0,
+            &[(0, 2), (0x80002000, 4), (0x40, 2), (0x18, 2)],
+        ),
+        (ExAction::ActiveCatchBlock, 0, &[(0x18, 2)]),
+        (
+            ExAction::CatchBlock32,
+            0,
+            &[(0, 2), (0x80002004, 4), (0x10000, 4), (0x20000, 4)],
+        ),
+        (
+            ExAction::Specification,
+            0,
+            &[
+                (2, 2),
+                (0x50, 4),
+                (0x28, 4),
+                (0x80002008, 4),
+                (0x8000200C, 4),
+            ],
+        ),
+        (ExAction::EndOfList, 0, &[]),
+    ];
+
+    /// Writes a table with two PC ranges and every action type in the given byte order.
+    fn table_bytes(endian: Endian) -> Vec<u8> {
+        let mut data = vec![];
+        mem_utils::write_uint16(&mut data, 0x3808, endian); //r29-r31 saved, large frame
+        mem_utils::write_uint16(&mut data, 0, endian);
+        for (start_pc, size, action_offset) in [(0x10, 0x20, 0x18), (0x40, 0x8, 0x96)] {
+            mem_utils::write_uint32(&mut data, start_pc, endian);
+            mem_utils::write_uint16(&mut data, size >> 2, endian);
+            mem_utils::write_uint16(&mut data, action_offset, endian);
+        }
+        mem_utils::write_uint32(&mut data, 0, endian);
+
+        for &(action_type, param, fields) in ACTIONS {
+            let ends_chain = matches!(action_type, ExAction::Terminate | ExAction::EndOfList);
+            mem_utils::write_byte(
+                &mut data,
+                action_type.to_int() as u8 | (ends_chain as u8) << 7,
+            );
+            mem_utils::write_byte(&mut data, param);
+            for &(value, size) in fields {
+                match size {
+                    2 => mem_utils::write_uint16(&mut data, value as u16, endian),
+                    _ => mem_utils::write_uint32(&mut data, value, endian),
+                }
+            }
+        }
+        data
+    }
+
+    fn options(endian: Endian) -> DecodeOptions {
+        DecodeOptions {
+            endian,
+            target: Target::PowerPc,
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        for endian in [Endian::Big, Endian::Little] {
+            let data = table_bytes(endian);
+            let table = decode_extab_with_options(&data, options(endian)).unwrap();
+            assert_eq!(table.pc_actions[1].action_offset, 0x96);
+            assert_eq!(table.exception_actions.len(), ACTIONS.len());
+            for (exaction, &(action_type, param, _)) in table.exception_actions.iter().zip(ACTIONS)
+            {
+                assert_eq!(exaction.action_type.to_int(), action_type.to_int());
+                assert_eq!(exaction.action_param, param);
+            }
+            assert_eq!(encode_extab(&table).unwrap(), data);
+            assert_eq!(validate_extab(&table), Ok(()));
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_references() {
+        let data = table_bytes(Endian::Big);
+        let mut table = decode_extab_with_options(&data, options(Endian::Big)).unwrap();
+        table.pc_actions[0].action_offset = 0x1A;
+        assert_eq!(
+            validate_extab(&table),
+            Err(ExtabEncodeError::UnknownActionOffset {
+                index: 0,
+                action_offset: 0x1A,
+            })
+        );
+        //The table can still be encoded as is
+        assert!(encode_extab(&table).is_ok());
+
+        let mut table = decode_extab_with_options(&data, options(Endian::Big)).unwrap();
+        table.exception_actions[11].bytes = vec![0, 0x98];
+        assert_eq!(
+            validate_extab(&table),
+            Err(ExtabEncodeError::UnknownBranchTarget {
+                index: 11,
+                target_offset: 0x98,
+            })
+        );
+    }
+
+    #[test]
+    fn relocatable_output() {
+        for endian in [Endian::Big, Endian::Little] {
+            let data = table_bytes(endian);
+            let mut table = decode_extab_with_options(&data, options(endian)).unwrap();
+            //Name the first dtor and the catch type, and leave the other addresses as is
+            let named = [0x1C, 0x9A];
+            for relocation in &mut table.relocations {
+                if named.contains(&relocation.offset) {
+                    relocation.symbol = Some(alloc::format!("sym_{:X}", relocation.offset));
+                }
+            }
+            let relocatable = encode_extab_relocatable(&table).unwrap();
+
+            let mut expected = data.clone();
+            for offset in named {
+                expected[offset as usize..offset as usize + 4].fill(0);
+            }
+            assert_eq!(relocatable.data, expected);
+            let offsets: Vec<u32> = relocatable.relocations.iter().map(|r| r.offset).collect();
+            assert_eq!(offsets, named);
+            assert_eq!(relocatable.relocations[0].field, RelocationField::Dtor);
+            assert_eq!(relocatable.relocations[1].field, RelocationField::CatchType);
+            assert_eq!(relocatable.relocations[1].symbol.as_deref(), Some("sym_9A"));
+        }
+    }
+
+    #[test]
+    fn rejects_relocation_outside_fields() {
+        let data = table_bytes(Endian::Big);
+        let mut table = decode_extab_with_options(&data, options(Endian::Big)).unwrap();
+        table.relocations[0].offset += 2;
+        table.relocations[0].symbol = Some("sym".into());
+        assert_eq!(
+            encode_extab_relocatable(&table).unwrap_err(),
+            ExtabEncodeError::InvalidRelocationOffset { offset: 0x1E }
+        );
+    }
+
+    #[test]
+    fn rejects_misplaced_actions() {
+        let data = table_bytes(Endian::Big);
+        let mut table = decode_extab_with_options(&data, options(Endian::Big)).unwrap();
+        table.exception_actions[1].action_offset += 2;
+        assert_eq!(
+            encode_extab(&table).unwrap_err(),
+            ExtabEncodeError::ActionOffsetMismatch {
+                index: 1,
+                expected: 0x20,
+                actual: 0x22,
+            }
+        );
+
+        let mut table = decode_extab_with_options(&data, options(Endian::Big)).unwrap();
+        table.exception_actions[0].bytes.pop();
+        assert_eq!(
+            encode_extab(&table).unwrap_err(),
+            ExtabEncodeError::InvalidActionDataSize {
+                index: 0,
+                expected: 6,
+                actual: 5,
+            }
+        );
+    }
+}
@@ -0,0 +1,265 @@
+use core::ops::RangeInclusive;
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ExtabFlagsError {
+    #[error("Invalid saved GPR count {0}, at most {max} registers can be saved", max = ExtabFlags::MAX_SAVED_REGS)]
+    InvalidGprSaveCount(u8),
+    #[error("Invalid saved FPR count {0}, at most {max} registers can be saved", max = ExtabFlags::MAX_SAVED_REGS)]
+    InvalidFprSaveCount(u8),
+}
+
+/// Header word of an exception table.
+///
+/// The first 16 bits hold the frame flags and the saved register counts, and the
+/// remaining 16 bits (the ET field) are kept as is. Bits without a known meaning are
+/// preserved, so converting to and from the raw values is lossless.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExtabFlags {
+    flag_val: u16, //0x0-1
+    et_field: u16, //0x2-3
+}
+
+impl ExtabFlags {
+    const HAS_ELF_VECTOR: u16 = 1 << 1;
+    const LARGE_FRAME: u16 = 1 << 3;
+    const HAS_FRAME_POINTER: u16 = 1 << 4;
+    const SAVED_CR: u16 = 1 << 5;
+    const FPR_SHIFT: u16 = 6; //bits 6-10
+    const GPR_SHIFT: u16 = 11; //bits 11-15
+    const SAVE_COUNT_MASK: u16 = 0b11111;
+
+    /// Mask of the flag bits with no known meaning (bits 0 and 2).
+    pub const UNKNOWN_BITS: u16 = (1 << 0) | (1 << 2);
+    /// Number of non-volatile registers (r14-r31 and f14-f31) a function can save.
+    pub const MAX_SAVED_REGS: u8 = 18;
+
+    /// Creates the flags from the two raw 16 bit values of the table header.
+    pub fn from_raw(flag_val: u16, et_field: u16) -> Self {
+        Self { flag_val, et_field }
+    }
+
+    /// Creates the flags from the 32 bit table header word.
+    pub fn from_bits(bits: u32) -> Self {
+        Self::from_raw((bits >> 16) as u16, bits as u16)
+    }
+
+    /// Returns the 32 bit table header word.
+    pub fn bits(&self) -> u32 {
+        ((self.flag_val as u32) << 16) | self.et_field as u32
+    }
+
+    /// Returns the raw flag value (first 16 bits of the header).
+    pub fn flag_val(&self) -> u16 {
+        self.flag_val
+    }
+
+    /// Returns the raw ET field (last 16 bits of the header).
+    pub fn et_field(&self) -> u16 {
+        self.et_field
+    }
+
+    pub fn set_et_field(&mut self, et_field: u16) {
+        self.et_field = et_field;
+    }
+
+    /// Returns the flag bits with no known meaning.
+    pub fn unknown_bits(&self) -> u16 {
+        self.flag_val & Self::UNKNOWN_BITS
+    }
+
+    pub fn set_unknown_bits(&mut self, bits: u16) {
+        self.flag_val = (self.flag_val & !Self::UNKNOWN_BITS) | (bits & Self::UNKNOWN_BITS);
+    }
+
+    fn flag(&self, mask: u16) -> bool {
+        (self.flag_val & mask) != 0
+    }
+
+    fn set_flag(&mut self, mask: u16, value: bool) {
+        if value {
+            self.flag_val |= mask;
+        } else {
+            self.flag_val &= !mask;
+        }
+    }
+
+    pub fn has_elf_vector(&self) -> bool {
+        self.flag(Self::HAS_ELF_VECTOR)
+    }
+
+    pub fn set_has_elf_vector(&mut self, value: bool) {
+        self.set_flag(Self::HAS_ELF_VECTOR, value);
+    }
+
+    pub fn large_frame(&self) -> bool {
+        self.flag(Self::LARGE_FRAME)
+    }
+
+    pub fn set_large_frame(&mut self, value: bool) {
+        self.set_flag(Self::LARGE_FRAME, value);
+    }
+
+    pub fn has_frame_pointer(&self) -> bool {
+        self.flag(Self::HAS_FRAME_POINTER)
+    }
+
+    pub fn set_has_frame_pointer(&mut self, value: bool) {
+        self.set_flag(Self::HAS_FRAME_POINTER, value);
+    }
+
+    pub fn saved_cr(&self) -> bool {
+        self.flag(Self::SAVED_CR)
+    }
+
+    pub fn set_saved_cr(&mut self, value: bool) {
+        self.set_flag(Self::SAVED_CR, value);
+    }
+
+    /// Returns the number of saved floating point registers.
+    pub fn fpr_save_count(&self) -> u8 {
+        ((self.flag_val >> Self::FPR_SHIFT) & Self::SAVE_COUNT_MASK) as u8
+    }
+
+    /// Returns the number of saved general purpose registers.
+    pub fn gpr_save_count(&self) -> u8 {
+        ((self.flag_val >> Self::GPR_SHIFT) & Self::SAVE_COUNT_MASK) as u8
+    }
+
+    /// Sets the number of saved floating point registers (saved from f31 downwards).
+    pub fn set_fpr_save_count(&mut self, count: u8) -> Result<(), ExtabFlagsError> {
+        if count > Self::MAX_SAVED_REGS {
+            return Err(ExtabFlagsError::InvalidFprSaveCount(count));
+        }
+        self.set_save_count(Self::FPR_SHIFT, count);
+        Ok(())
+    }
+
+    /// Sets the number of saved general purpose registers (saved from r31 downwards).
+    pub fn set_gpr_save_count(&mut self, count: u8) -> Result<(), ExtabFlagsError> {
+        if count > Self::MAX_SAVED_REGS {
+            return Err(ExtabFlagsError::InvalidGprSaveCount(count));
+        }
+        self.set_save_count(Self::GPR_SHIFT, count);
+        Ok(())
+    }
+
+    fn set_save_count(&mut self, shift: u16, count: u8) {
+        let mask = Self::SAVE_COUNT_MASK << shift;
+        self.flag_val = (self.flag_val & !mask) | (((count as u16) << shift) & mask);
+    }
+
+    /// Returns the range of saved floating point register numbers, like `28..=31` for
+    /// f28-f31. The range is empty if no registers are saved.
+    pub fn saved_fprs(&self) -> RangeInclusive<u8> {
+        (32 - self.fpr_save_count())..=31
+    }
+
+    /// Returns the range of saved general purpose register numbers, like `28..=31` for
+    /// r28-r31. The range is empty if no registers are saved.
+    pub fn saved_gprs(&self) -> RangeInclusive<u8> {
+        (32 - self.gpr_save_count())..=31
+    }
+
+    /// Checks that the flags describe a possible frame. Since the save counts are stored
+    /// in 5 bits, they can hold values above the number of non-volatile registers.
+    pub fn validate(&self) -> Result<(), ExtabFlagsError> {
+        if self.gpr_save_count() > Self::MAX_SAVED_REGS {
+            return Err(ExtabFlagsError::InvalidGprSaveCount(self.gpr_save_count()));
+        }
+        if self.fpr_save_count() > Self::MAX_SAVED_REGS {
+            return Err(ExtabFlagsError::InvalidFprSaveCount(self.fpr_save_count()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raw_round_trip() {
+        for bits in [0x0000_0000, 0x2008_0000, 0x18A9_1234, 0xFFFF_FFFF] {
+            let flags = ExtabFlags::from_bits(bits);
+            assert_eq!(flags.bits(), bits);
+            assert_eq!(
+                ExtabFlags::from_raw(flags.flag_val(), flags.et_field()),
+                flags
+            );
+        }
+
+        let flags = ExtabFlags::from_raw(0x18A9, 0x1234);
+        assert_eq!(flags.unknown_bits(), 0x1);
+        assert_eq!(flags.et_field(), 0x1234);
+        assert!(flags.large_frame());
+        assert!(flags.saved_cr());
+        assert!(!flags.has_frame_pointer());
+        assert!(!flags.has_elf_vector());
+        assert_eq!(flags.gpr_save_count(), 3);
+        assert_eq!(flags.fpr_save_count(), 2);
+        assert_eq!(flags.saved_gprs(), 29..=31);
+        assert_eq!(flags.saved_fprs(), 30..=31);
+    }
+
+    #[test]
+    fn setters_keep_other_bits() {
+        let mut flags = ExtabFlags::from_raw(0x0005, 0xBEEF);
+        flags.set_has_frame_pointer(true);
+        flags.set_has_elf_vector(true);
+        flags.set_gpr_save_count(4).unwrap();
+        flags.set_fpr_save_count(1).unwrap();
+        assert_eq!(flags.unknown_bits(), 0x5);
+        assert_eq!(flags.et_field(), 0xBEEF);
+        assert_eq!(flags.flag_val(), 0x2057);
+
+        flags.set_unknown_bits(0);
+        flags.set_has_elf_vector(false);
+        assert_eq!(flags.flag_val(), 0x2050);
+        assert_eq!(ExtabFlags::default().saved_gprs().count(), 0);
+    }
+
+    #[test]
+    fn save_counts_are_limited() {
+        let mut flags = ExtabFlags::default();
+        flags
+            .set_gpr_save_count(ExtabFlags::MAX_SAVED_REGS)
+            .unwrap();
+        flags
+            .set_fpr_save_count(ExtabFlags::MAX_SAVED_REGS)
+            .unwrap();
+        assert_eq!(flags.saved_gprs(), 14..=31);
+        assert_eq!(flags.saved_fprs(), 14..=31);
+        assert_eq!(flags.validate(), Ok(()));
+
+        let too_many = ExtabFlags::MAX_SAVED_REGS + 1;
+        assert_eq!(
+            flags.set_gpr_save_count(too_many),
+            Err(ExtabFlagsError::InvalidGprSaveCount(too_many))
+        );
+        assert_eq!(
+            flags.set_fpr_save_count(too_many),
+            Err(ExtabFlagsError::InvalidFprSaveCount(too_many))
+        );
+        //Failed setters leave the flags unchanged
+        assert_eq!(flags.gpr_save_count(), ExtabFlags::MAX_SAVED_REGS);
+        assert_eq!(flags.fpr_save_count(), ExtabFlags::MAX_SAVED_REGS);
+    }
+
+    #[test]
+    fn validate_checks_raw_counts() {
+        //The 5 bit count fields can hold up to 31
+        let flags = ExtabFlags::from_raw(31 << 11, 0);
+        assert_eq!(
+            flags.validate(),
+            Err(ExtabFlagsError::InvalidGprSaveCount(31))
+        );
+        let flags = ExtabFlags::from_raw(19 << 6, 0);
+        assert_eq!(
+            flags.validate(),
+            Err(ExtabFlagsError::InvalidFprSaveCount(19))
+        );
+        assert_eq!(ExtabFlags::from_raw(0x0234, 0).validate(), Ok(()));
+    }
+}
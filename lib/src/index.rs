@@ -0,0 +1,331 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use thiserror::Error;
+
+use crate::mem_utils;
+use crate::ExtabFlags;
+use crate::{decode_extab_with_options, DecodeOptions, ExceptionTableData, ExtabDecodeError};
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ExtabIndexError {
+    #[error("Truncated .extabindex entry: {0}")]
+    Truncated(ExtabDecodeError),
+    #[error("Index entry {index} at 0x{address:08X} is not sorted by function address")]
+    Unsorted { index: u32, address: u32 },
+    #[error("Index entry {index} at 0x{address:08X} overlaps with the previous function")]
+    Overlapping { index: u32, address: u32 },
+    #[error("Index entry {index} points outside of the extab data (address 0x{address:08X})")]
+    InvalidExtabAddress { index: u32, address: u32 },
+    #[error("Failed to decode the table of index entry {index}: {error}")]
+    Decode { index: u32, error: ExtabDecodeError },
+}
+
+/// Struct for an entry of the .extabindex section.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ExtabIndexEntry {
+    pub function_address: u32, //0x0
+    pub size_word: u32,        //0x4, function size with the flag bits in the low bits
+    pub extab_address: u32,    //0x8
+}
+
+impl ExtabIndexEntry {
+    /// Size of an entry in bytes.
+    pub const SIZE: usize = 12;
+    /// Mask of the flag bits of the size word. Function sizes are always a multiple of 4,
+    /// so the low bits are free to hold flags.
+    pub const FLAG_MASK: u32 = 0b11;
+    /// Flag set when the table is stored directly in the extab address field.
+    pub const DIRECT_STORE: u32 = 1 << 0;
+
+    /// Returns the size of the function in bytes, without the flag bits.
+    pub fn function_size(&self) -> u32 {
+        self.size_word & !Self::FLAG_MASK
+    }
+
+    /// Returns the flag bits of the size word.
+    pub fn flags(&self) -> u32 {
+        self.size_word & Self::FLAG_MASK
+    }
+
+    /// Returns whether the table is stored directly in the extab address field instead of
+    /// in the .extab section.
+    pub fn is_direct_store(&self) -> bool {
+        (self.size_word & Self::DIRECT_STORE) != 0
+    }
+
+    /// Returns the address right after the end of the function.
+    pub fn function_end(&self) -> u32 {
+        self.function_address.wrapping_add(self.function_size())
+    }
+}
+
+/// Decodes the entries of the provided .extabindex data.
+///
+/// Returns an error if the data is truncated, or if the entries are not sorted by function
+/// address or overlap with each other.
+pub fn decode_extabindex(data: &[u8]) -> Result<Vec<ExtabIndexEntry>, ExtabIndexError> {
+    decode_extabindex_with_options(data, DecodeOptions::default())
+}
+
+/// Decodes the entries of the provided .extabindex data with the given byte order. See
+/// `decode_extabindex`.
+pub fn decode_extabindex_with_options(
+    data: &[u8],
+    options: DecodeOptions,
+) -> Result<Vec<ExtabIndexEntry>, ExtabIndexError> {
+    let endian = options.endian;
+    let mut entries: Vec<ExtabIndexEntry> = vec![];
+    let mut offset: usize = 0;
+    while offset < data.len() {
+        let mut read = |field| mem_utils::read_uint32(data, &mut offset, true, field, endian);
+        let entry = ExtabIndexEntry {
+            function_address: read("function_address").map_err(ExtabIndexError::Truncated)?,
+            size_word: read("function_size").map_err(ExtabIndexError::Truncated)?,
+            extab_address: read("extab_address").map_err(ExtabIndexError::Truncated)?,
+        };
+
+        if let Some(previous) = entries.last() {
+            let index = entries.len() as u32;
+            let address = entry.function_address;
+            if address < previous.function_address {
+                return Err(ExtabIndexError::Unsorted { index, address });
+            }
+            if address < previous.function_end() {
+                return Err(ExtabIndexError::Overlapping { index, address });
+            }
+        }
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+/// Decodes the table of every index entry from the provided extab data, which starts at
+/// `extab_address`, and pairs each entry with its table.
+///
+/// Tables are stored back to back, so each table ends where the next one starts. For
+/// entries with a directly stored table, the table only holds the header flags.
+pub fn decode_indexed_tables(
+    entries: &[ExtabIndexEntry],
+    extab: &[u8],
+    extab_address: u32,
+) -> Result<Vec<(ExtabIndexEntry, ExceptionTableData)>, ExtabIndexError> {
+    decode_indexed_tables_with_options(entries, extab, extab_address, DecodeOptions::default())
+}
+
+/// Pairs each index entry with its table using the given byte order and target. See
+/// `decode_indexed_tables`.
+pub fn decode_indexed_tables_with_options(
+    entries: &[ExtabIndexEntry],
+    extab: &[u8],
+    extab_address: u32,
+    options: DecodeOptions,
+) -> Result<Vec<(ExtabIndexEntry, ExceptionTableData)>, ExtabIndexError> {
+    let mut starts: Vec<u32> = entries
+        .iter()
+        .filter(|entry| !entry.is_direct_store())
+        .map(|entry| entry.extab_address.wrapping_sub(extab_address))
+        .collect();
+    starts.sort_unstable();
+    starts.dedup();
+
+    let mut tables = vec![];
+    for (i, entry) in entries.iter().enumerate() {
+        let index = i as u32;
+        if entry.is_direct_store() {
+            let mut table = ExceptionTableData::new();
+            table.options = options;
+            table.flags = ExtabFlags::from_bits(entry.extab_address);
+            tables.push((*entry, table));
+            continue;
+        }
+
+        let start = entry.extab_address.wrapping_sub(extab_address);
+        if start as usize >= extab.len() {
+            return Err(ExtabIndexError::InvalidExtabAddress {
+                index,
+                address: entry.extab_address,
+            });
+        }
+        let end = match starts.iter().find(|&&offset| offset > start) {
+            Some(&offset) => offset,
+            None => extab.len() as u32,
+        };
+        let table = decode_extab_with_options(&extab[start as usize..end as usize], options)
+            .map_err(|error| ExtabIndexError::Decode { index, error })?;
+        tables.push((*entry, table));
+    }
+    Ok(tables)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Endian, ErrorLocation, Target};
+
+    /// Table with one PC range and a DestroyLocal action.
+    const TABLE: [u8; 24] = [
+        0x00, 0x00, 0x00, 0x00, //Header
+        0x00, 0x00, 0x00, 0x04, 0x00, 0x02, 0x00, 0x10, //PC action
+        0x00, 0x00, 0x00, 0x00, //Terminator
+        0x82, 0x00, 0x00, 0x08, 0x80, 0x00, 0x10, 0x00, //DestroyLocal
+    ];
+
+    fn index_data(entries: &[[u32; 3]], endian: Endian) -> Vec<u8> {
+        let mut data = vec![];
+        for entry in entries {
+            for value in entry {
+                match endian {
+                    Endian::Big => data.extend_from_slice(&value.to_be_bytes()),
+                    Endian::Little => data.extend_from_slice(&value.to_le_bytes()),
+                }
+            }
+        }
+        data
+    }
+
+    #[test]
+    fn decodes_entries() {
+        let raw = [
+            [0x80003100, 0x40, 0x80005000],
+            [0x80003140, 0x21, 0x00080000],
+        ];
+        for endian in [Endian::Big, Endian::Little] {
+            let options = DecodeOptions {
+                endian,
+                target: Target::PowerPc,
+            };
+            let entries =
+                decode_extabindex_with_options(&index_data(&raw, endian), options).unwrap();
+            assert_eq!(entries.len(), 2);
+            assert_eq!(
+                entries[0],
+                ExtabIndexEntry {
+                    function_address: 0x80003100,
+                    size_word: 0x40,
+                    extab_address: 0x80005000,
+                }
+            );
+            assert_eq!(entries[0].function_end(), 0x80003140);
+            assert!(!entries[0].is_direct_store());
+
+            //The flag bits are not part of the size
+            assert_eq!(entries[1].function_size(), 0x20);
+            assert_eq!(entries[1].flags(), ExtabIndexEntry::DIRECT_STORE);
+            assert!(entries[1].is_direct_store());
+        }
+        let entry = ExtabIndexEntry {
+            function_address: 0,
+            size_word: 0x12,
+            extab_address: 0,
+        };
+        assert_eq!(entry.flags(), 0x2);
+        assert_eq!(entry.function_size(), 0x10);
+        assert!(!entry.is_direct_store());
+    }
+
+    #[test]
+    fn rejects_invalid_entries() {
+        let data = index_data(&[[0x80003100, 0x40, 0x80005000]], Endian::Big);
+        assert_eq!(
+            decode_extabindex(&data[..10]),
+            Err(ExtabIndexError::Truncated(
+                ExtabDecodeError::UnexpectedEof {
+                    needed: 4,
+                    available: 2,
+                    location: ErrorLocation::new(8, "extab_address"),
+                }
+            ))
+        );
+        //A 12 byte entry with a partial entry after it
+        let mut long = data.clone();
+        long.extend_from_slice(&[0; 6]);
+        assert!(matches!(
+            decode_extabindex(&long),
+            Err(ExtabIndexError::Truncated(_))
+        ));
+
+        let unsorted = [[0x80003100, 0x40, 0], [0x80003000, 0x40, 0]];
+        assert_eq!(
+            decode_extabindex(&index_data(&unsorted, Endian::Big)),
+            Err(ExtabIndexError::Unsorted {
+                index: 1,
+                address: 0x80003000,
+            })
+        );
+        let overlapping = [[0x80003100, 0x40, 0], [0x80003120, 0x40, 0]];
+        assert_eq!(
+            decode_extabindex(&index_data(&overlapping, Endian::Big)),
+            Err(ExtabIndexError::Overlapping {
+                index: 1,
+                address: 0x80003120,
+            })
+        );
+    }
+
+    #[test]
+    fn pairs_tables() {
+        let mut extab = TABLE.to_vec();
+        extab.extend_from_slice(&TABLE);
+        let entries = [
+            ExtabIndexEntry {
+                function_address: 0x80003100,
+                size_word: 0x20,
+                extab_address: 0x80005018,
+            },
+            ExtabIndexEntry {
+                function_address: 0x80003120,
+                size_word: 0x21,
+                extab_address: 0x18080000,
+            },
+            ExtabIndexEntry {
+                function_address: 0x80003140,
+                size_word: 0x20,
+                extab_address: 0x80005000,
+            },
+        ];
+        let tables = decode_indexed_tables(&entries, &extab, 0x80005000).unwrap();
+        assert_eq!(tables.len(), 3);
+        assert_eq!(tables[0].0, entries[0]);
+        assert_eq!(tables[0].1.exception_actions.len(), 1);
+        //Directly stored tables only have the header
+        assert_eq!(tables[1].1.flags.bits(), 0x18080000);
+        assert!(tables[1].1.pc_actions.is_empty());
+        //The first table ends where the second one starts
+        assert_eq!(tables[2].1.exception_actions.len(), 1);
+        assert_eq!(tables[2].1.exception_actions[0].action_offset, 0x10);
+    }
+
+    #[test]
+    fn rejects_entries_outside_extab() {
+        let entries = [ExtabIndexEntry {
+            function_address: 0x80003100,
+            size_word: 0x20,
+            extab_address: 0x80005018,
+        }];
+        assert_eq!(
+            decode_indexed_tables(&entries, &TABLE, 0x80005000).unwrap_err(),
+            ExtabIndexError::InvalidExtabAddress {
+                index: 0,
+                address: 0x80005018,
+            }
+        );
+        //Addresses before the start of the data wrap around
+        let entries = [ExtabIndexEntry {
+            extab_address: 0x80004FF0,
+            ..entries[0]
+        }];
+        assert!(matches!(
+            decode_indexed_tables(&entries, &TABLE, 0x80005000),
+            Err(ExtabIndexError::InvalidExtabAddress { .. })
+        ));
+
+        let entries = [ExtabIndexEntry {
+            extab_address: 0x80005000,
+            ..entries[0]
+        }];
+        assert!(matches!(
+            decode_indexed_tables(&entries, &TABLE[..20], 0x80005000),
+            Err(ExtabIndexError::Decode { index: 0, .. })
+        ));
+    }
+}
@@ -1,27 +1,200 @@
 #![no_std]
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
 
 use alloc::string::String;
 use alloc::{format, vec};
 use alloc::vec::Vec;
+use core::fmt;
 use thiserror::Error;
 
+mod asm;
+mod builder;
+mod demangle;
+mod dol;
+mod dot;
+#[cfg(feature = "elf")]
+mod elf;
+mod encoder;
+mod flags;
+mod index;
+mod listing;
+mod map;
 mod mem_utils;
+mod options;
+mod rel;
+mod render;
+mod scan;
+#[cfg(feature = "serde")]
+mod serialize;
+mod symbols;
+mod typeinfo;
+mod view;
 
-#[derive(Error, Debug)]
+pub use asm::*;
+pub use builder::*;
+pub use demangle::*;
+pub use dol::*;
+pub use dot::*;
+#[cfg(feature = "elf")]
+pub use elf::*;
+pub use encoder::*;
+pub use flags::*;
+pub use index::*;
+pub use listing::*;
+pub use map::*;
+pub use options::*;
+pub use rel::*;
+pub use render::*;
+pub use scan::*;
+#[cfg(feature = "serde")]
+pub use serialize::*;
+pub use symbols::*;
+pub use typeinfo::*;
+pub use view::*;
+
+/// Location in the table where a decoding error happened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ErrorLocation {
+    /// Byte offset into the table data.
+    pub offset: u32,
+    /// Index of the PC action being decoded, if any.
+    pub pc_action_index: Option<u32>,
+    /// Index of the exception action being decoded, if any.
+    pub action_index: Option<u32>,
+    /// Name of the field being read. Empty if the error isn't tied to a single field.
+    pub field: &'static str,
+}
+
+impl ErrorLocation {
+    pub fn new(offset: u32, field: &'static str) -> Self {
+        Self {
+            offset,
+            pc_action_index: None,
+            action_index: None,
+            field,
+        }
+    }
+}
+
+impl fmt::Display for ErrorLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "at offset 0x{:X}", self.offset)?;
+        if let Some(index) = self.pc_action_index {
+            write!(f, ", PC action {index}")?;
+        }
+        if let Some(index) = self.action_index {
+            write!(f, ", action {index}")?;
+        }
+        if !self.field.is_empty() {
+            write!(f, ", field '{}'", self.field)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
 pub enum ExtabDecodeError {
-    #[error("Data array should at least be 8 bytes long. Given array is {0} bytes long.")]
-    ArrayTooSmall(u32),
-    #[error("Invalid action value {0} at offset 0x{1:X}")]
-    InvalidActionValue(u32, u32),
+    #[error("Data array should at least be 8 bytes long. Given array is {length} bytes long.")]
+    ArrayTooSmall {
+        length: u32,
+        location: ErrorLocation,
+    },
+    #[error("Invalid action value {value} {location}")]
+    InvalidActionValue { value: u32, location: ErrorLocation },
     #[error("Table is 8 bytes long but terminator is not zero.")]
-    InvalidSmallTableTerminator,
-    #[error("Internal error")]
-    Internal,
+    InvalidSmallTableTerminator { location: ErrorLocation },
+    #[error("Unexpected end of data {location}: needed {needed} bytes, {available} available")]
+    UnexpectedEof {
+        needed: u32,
+        available: u32,
+        location: ErrorLocation,
+    },
+    #[error("PC action list is not terminated by a zero value ({location})")]
+    MissingPcTerminator { location: ErrorLocation },
+    #[error("Internal error {location}")]
+    Internal { location: ErrorLocation },
+}
+
+impl ExtabDecodeError {
+    /// Returns the location in the table where the error happened.
+    pub fn location(&self) -> &ErrorLocation {
+        match self {
+            ExtabDecodeError::ArrayTooSmall { location, .. }
+            | ExtabDecodeError::InvalidActionValue { location, .. }
+            | ExtabDecodeError::InvalidSmallTableTerminator { location }
+            | ExtabDecodeError::UnexpectedEof { location, .. }
+            | ExtabDecodeError::MissingPcTerminator { location }
+            | ExtabDecodeError::Internal { location } => location,
+        }
+    }
+
+    fn location_mut(&mut self) -> &mut ErrorLocation {
+        match self {
+            ExtabDecodeError::ArrayTooSmall { location, .. }
+            | ExtabDecodeError::InvalidActionValue { location, .. }
+            | ExtabDecodeError::InvalidSmallTableTerminator { location }
+            | ExtabDecodeError::UnexpectedEof { location, .. }
+            | ExtabDecodeError::MissingPcTerminator { location }
+            | ExtabDecodeError::Internal { location } => location,
+        }
+    }
+
+    fn with_pc_action_index(mut self, index: usize) -> Self {
+        self.location_mut().pc_action_index = Some(index as u32);
+        self
+    }
+
+    fn with_action_index(mut self, index: usize) -> Self {
+        self.location_mut().action_index = Some(index as u32);
+        self
+    }
+
+    /// Moves the error offset by the given amount, for errors found in a sub-slice of the table.
+    fn relocate(mut self, base: u32) -> Self {
+        let location = self.location_mut();
+        location.offset = location.offset.wrapping_add(base);
+        self
+    }
+
+    /// Renders a short hexdump of the given table data around the error offset, with the
+    /// failing byte marked. The data should be the same slice that was passed to the decoder.
+    pub fn hexdump(&self, data: &[u8]) -> String {
+        const ROW_SIZE: usize = 16;
+        let offset = self.location().offset as usize;
+        let error_row = offset / ROW_SIZE;
+        let first_row = error_row.saturating_sub(1);
+        let last_row = error_row + 1;
+
+        let mut sb = String::from("");
+        for row in first_row..=last_row {
+            let row_start = row * ROW_SIZE;
+            if row_start >= data.len() && row != error_row {
+                break;
+            }
+            let row_end = data.len().min(row_start + ROW_SIZE);
+            sb += format!("{row_start:08X}:").as_str();
+            for byte in data.get(row_start..row_end).unwrap_or(&[]) {
+                sb += format!(" {byte:02X}").as_str();
+            }
+            sb += "\n";
+
+            //Mark the failing byte. If the error is past the end of the data, the marker
+            //points to where the missing byte would be.
+            if row == error_row {
+                let column = offset - row_start;
+                sb += format!("{:width$}^^\n", "", width = 10 + column * 3).as_str();
+            }
+        }
+        sb
+    }
 }
 
 /// Enum holding the data for each action type.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "action"))]
 pub enum ExActionData {
     EndOfList,
     Branch {
@@ -105,8 +278,125 @@ pub enum ExActionData {
     },
 }
 
+/// Register used as the base for frame offsets.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FrameBase {
+    /// Stack pointer (r1)
+    Sp,
+    /// Frame pointer, used when the function has one
+    Fp,
+}
+
+impl fmt::Display for FrameBase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FrameBase::Sp => write!(f, "SP"),
+            FrameBase::Fp => write!(f, "FP"),
+        }
+    }
+}
+
+/// Location of an action operand, either in the stack frame or in a register.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Location {
+    FrameOffset { base: FrameBase, offset: u32 },
+    Register(u16),
+}
+
+impl Location {
+    /// Returns a register location if `is_register` is set, and a frame offset otherwise.
+    fn from_mode(is_register: bool, value: u16, base: FrameBase) -> Self {
+        if is_register {
+            Location::Register(value)
+        } else {
+            Location::FrameOffset {
+                base,
+                offset: value as u32,
+            }
+        }
+    }
+}
+
+//Uses the PowerPC register names, see `Target::format_location` for the target names
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Location::FrameOffset { base, offset } => write!(f, "{offset:#X}({base})"),
+            Location::Register(reg) => write!(f, "r{reg}"),
+        }
+    }
+}
+
+impl ExActionData {
+    /// Returns the location of the object, local, pointer or catch info the action operates on,
+    /// if the action has one.
+    ///
+    /// The action param of the action entry selects between frame offsets and registers, and
+    /// `base` is the register frame offsets are relative to (see `ExceptionTableData::frame_base`).
+    pub fn object_location(&self, action_param: u8, base: FrameBase) -> Option<Location> {
+        //Bit 7 of the param selects a register for most actions, while the conditional
+        //actions use bit 6 since bit 7 is used by the condition.
+        let bit7 = (action_param >> 7) != 0;
+        let bit6 = ((action_param >> 6) & 1) != 0;
+        let location = match *self {
+            ExActionData::DestroyLocal { local_offset, .. }
+            | ExActionData::DestroyLocalCond { local_offset, .. } => {
+                Location::from_mode(false, local_offset, base)
+            }
+            ExActionData::DestroyLocalArray { local_array, .. } => {
+                Location::from_mode(false, local_array, base)
+            }
+            ExActionData::DestroyLocalPointer { local_pointer, .. } => {
+                Location::from_mode(bit7, local_pointer, base)
+            }
+            ExActionData::DestroyBase { object_pointer, .. }
+            | ExActionData::DestroyMember { object_pointer, .. }
+            | ExActionData::DestroyMemberArray { object_pointer, .. }
+            | ExActionData::DeletePointer { object_pointer, .. } => {
+                Location::from_mode(bit7, object_pointer, base)
+            }
+            ExActionData::DestroyMemberCond { object_pointer, .. }
+            | ExActionData::DeletePointerCond { object_pointer, .. } => {
+                Location::from_mode(bit6, object_pointer, base)
+            }
+            ExActionData::CatchBlock { cinfo_ref, .. }
+            | ExActionData::ActiveCatchBlock { cinfo_ref } => {
+                Location::from_mode(false, cinfo_ref, base)
+            }
+            ExActionData::Specification { cinfo_ref, .. }
+            | ExActionData::CatchBlock32 { cinfo_ref, .. } => Location::FrameOffset {
+                base,
+                offset: cinfo_ref,
+            },
+            ExActionData::EndOfList | ExActionData::Branch { .. } | ExActionData::Terminate => {
+                return None
+            }
+        };
+        Some(location)
+    }
+
+    /// Returns the location of the condition flag for conditional actions.
+    ///
+    /// See `object_location` for the meaning of the parameters.
+    pub fn condition_location(&self, action_param: u8, base: FrameBase) -> Option<Location> {
+        let location = match *self {
+            //The whole param is used as the mode for this action
+            ExActionData::DestroyLocalCond { condition, .. } => {
+                Location::from_mode(action_param != 0, condition, base)
+            }
+            ExActionData::DestroyMemberCond { condition, .. }
+            | ExActionData::DeletePointerCond { condition, .. } => {
+                Location::from_mode((action_param >> 7) != 0, condition, base)
+            }
+            _ => return None,
+        };
+        Some(location)
+    }
+}
+
 /// Base enum for exception actions.
 #[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ExAction {
     EndOfList,
     Branch,
@@ -200,49 +490,112 @@ impl ExAction {
     fn convert_to_string(&self) -> String {
         String::from(Self::ACTION_NAMES[self.to_int() as usize])
     }
-}
 
-/// Struct for exception actions.
-#[derive(Debug, Clone)]
-pub struct ExceptionAction {
-    //General values
-    pub action_offset: u32,
-    pub action_type: ExAction, //0x0
-    pub action_param: u8,      //0x1
-    pub has_end_bit: bool,     //true if action type byte has bit 7 set (type & 0x80)
-    pub bytes: Vec<u8>,
-}
+    /// Returns whether this action has a destuctor reference or not.
+    pub(crate) fn has_dtor_ref(&self) -> bool {
+        !matches!(
+            self,
+            ExAction::EndOfList
+                | ExAction::Branch
+                | ExAction::CatchBlock
+                | ExAction::ActiveCatchBlock
+                | ExAction::Terminate
+                | ExAction::Specification
+                | ExAction::CatchBlock32
+        )
+    }
 
-impl ExceptionAction {
-    pub fn new() -> Self {
-        Self {
-            action_offset: 0,
-            action_type: ExAction::EndOfList,
-            action_param: 0,
-            has_end_bit: false,
-            bytes: vec![],
+    /// Returns the size of the data following the action type and param bytes for this
+    /// action type. `spec_count` is the number of types in a specification action.
+    pub(crate) fn data_size(&self, spec_count: u16) -> usize {
+        match self {
+            ExAction::EndOfList | ExAction::Terminate => 0,
+            ExAction::Branch | ExAction::ActiveCatchBlock => 2,
+            ExAction::DestroyLocal | ExAction::DestroyLocalPointer | ExAction::DeletePointer => 6,
+            ExAction::DestroyLocalCond
+            | ExAction::DestroyLocalArray
+            | ExAction::DestroyBase
+            | ExAction::DestroyMember
+            | ExAction::DeletePointerCond
+            | ExAction::CatchBlock => 10,
+            ExAction::DestroyMemberCond | ExAction::CatchBlock32 => 14,
+            ExAction::DestroyMemberArray => 18,
+            //The base size plus the array of type addresses
+            ExAction::Specification => 10 + (spec_count as usize) * 4,
         }
     }
 
-    /// Returns whether this action has a destuctor reference or not.
-    pub fn has_dtor_ref(&self) -> bool {
-        match self.action_type {
-            ExAction::EndOfList
-            | ExAction::Branch
-            | ExAction::CatchBlock
-            | ExAction::ActiveCatchBlock
-            | ExAction::Terminate
-            | ExAction::Specification
-            | ExAction::CatchBlock32 => false,
-            _ => true,
+    /// Returns the name and size of each field of the data following the action type and
+    /// param bytes, in order. `spec_count` is the number of types in a specification action.
+    pub(crate) fn field_layout(&self, spec_count: u16) -> Vec<(&'static str, usize)> {
+        let mut fields = match self {
+            ExAction::EndOfList | ExAction::Terminate => vec![],
+            ExAction::Branch => vec![("target_offset", 2)],
+            ExAction::DestroyLocal => vec![("local_offset", 2), ("dtor_address", 4)],
+            ExAction::DestroyLocalCond => vec![
+                ("condition", 2),
+                ("local_offset", 2),
+                ("unk4", 2),
+                ("dtor_address", 4),
+            ],
+            ExAction::DestroyLocalPointer => vec![("local_pointer", 2), ("dtor_address", 4)],
+            ExAction::DestroyLocalArray => vec![
+                ("local_array", 2),
+                ("elements", 2),
+                ("element_size", 2),
+                ("dtor_address", 4),
+            ],
+            ExAction::DestroyBase | ExAction::DestroyMember => vec![
+                ("object_pointer", 2),
+                ("member_offset", 4),
+                ("dtor_address", 4),
+            ],
+            ExAction::DestroyMemberCond => vec![
+                ("condition", 2),
+                ("object_pointer", 2),
+                ("member_offset", 4),
+                ("unk8", 2),
+                ("dtor_address", 4),
+            ],
+            ExAction::DestroyMemberArray => vec![
+                ("object_pointer", 2),
+                ("member_offset", 4),
+                ("elements", 4),
+                ("element_size", 4),
+                ("dtor_address", 4),
+            ],
+            ExAction::DeletePointer => vec![("object_pointer", 2), ("dtor_address", 4)],
+            ExAction::DeletePointerCond => vec![
+                ("condition", 2),
+                ("object_pointer", 2),
+                ("unk4", 2),
+                ("dtor_address", 4),
+            ],
+            ExAction::CatchBlock => vec![
+                ("unk0", 2),
+                ("catch_type", 4),
+                ("catch_pc_offset", 2),
+                ("cinfo_ref", 2),
+            ],
+            ExAction::ActiveCatchBlock => vec![("cinfo_ref", 2)],
+            ExAction::Specification => vec![("specs", 2), ("pc_offset", 4), ("cinfo_ref", 4)],
+            ExAction::CatchBlock32 => vec![
+                ("unk0", 2),
+                ("catch_type", 4),
+                ("catch_pc_offset", 4),
+                ("cinfo_ref", 4),
+            ],
+        };
+        if matches!(self, ExAction::Specification) {
+            fields.extend((0..spec_count).map(|_| ("spec", 4)));
         }
+        fields
     }
 
     /// Calculates the offset of the dtor function address value in this action entry.
     /// If the entry does not have one, this function returns none.
     fn get_dtor_address_value_offset(&self) -> Option<u32> {
-        let offset: u32 =
-        match self.action_type {
+        let offset: u32 = match self {
             ExAction::DestroyLocal => 2,
             ExAction::DestroyLocalCond => 6,
             ExAction::DestroyLocalPointer => 2,
@@ -259,8 +612,12 @@ impl ExceptionAction {
         Some(offset)
     }
 
-    /// Returns the relocation data for the dtor function in this action entry, if any.
-    pub fn get_dtor_relocation(&self) -> Option<(u32, u32)> {
+    /// Returns the relocation data for the dtor function in the given action data, if any.
+    pub(crate) fn get_dtor_relocation(
+        &self,
+        bytes: &[u8],
+        options: DecodeOptions,
+    ) -> Option<(u32, u32)> {
         if !self.has_dtor_ref() {
             //If the action entry doesn't have a dtor reference, return none
             return None;
@@ -273,34 +630,120 @@ impl ExceptionAction {
             }
         };
 
-        let address: u32 = mem_utils::read_uint32(&self.bytes, &mut (offset as i32), true);
+        let address: u32 = mem_utils::read_uint32(
+            bytes,
+            &mut (offset as usize),
+            true,
+            "dtor_address",
+            options.endian,
+        )
+        .ok()?;
         Some((offset, address))
     }
 
-    /// Decodes the action data from the byte array depending on the set action type, and converts it
-    /// to an ExActionData enum containing the decoded data.
-    pub fn get_exaction_data(&self) -> ExActionData {
-        let mut offset: i32 = 0;
+    /// Returns the offsets of the relocated address fields in the given action data, along
+    /// with what each field holds.
+    pub(crate) fn relocation_fields(
+        &self,
+        bytes: &[u8],
+        options: DecodeOptions,
+    ) -> Vec<(u32, RelocationField)> {
+        let mut fields = vec![];
+        if let Some(offset) = self.get_dtor_address_value_offset() {
+            fields.push((offset, RelocationField::Dtor));
+        }
+        match self {
+            ExAction::CatchBlock | ExAction::CatchBlock32 => {
+                fields.push((2, RelocationField::CatchType));
+            }
+            ExAction::Specification => {
+                //The type list follows the count, PC offset and cinfo values
+                let specs = mem_utils::read_uint16(bytes, &mut 0, false, "specs", options.endian)
+                    .unwrap_or(0);
+                for i in 0..specs as u32 {
+                    fields.push((10 + i * 4, RelocationField::SpecType));
+                }
+            }
+            _ => {}
+        }
+        fields
+    }
 
-        match self.action_type {
+    /// Returns the relocations for the given action data, for an action entry at the given offset.
+    pub(crate) fn get_relocations(
+        &self,
+        bytes: &[u8],
+        action_offset: u32,
+        options: DecodeOptions,
+    ) -> Result<Vec<Relocation>, ExtabDecodeError> {
+        //The byte array starts after the action type and param bytes
+        let base = action_offset.wrapping_add(2);
+        let mut relocations = vec![];
+        for (offset, field) in self.relocation_fields(bytes, options) {
+            let field_name = match field {
+                RelocationField::Dtor => "dtor_address",
+                RelocationField::CatchType => "catch_type",
+                RelocationField::SpecType => "spec",
+            };
+            let address = mem_utils::read_uint32(
+                bytes,
+                &mut (offset as usize),
+                true,
+                field_name,
+                options.endian,
+            )
+            .map_err(|e| e.relocate(base))?;
+            relocations.push(Relocation::new(base + offset, address, field));
+        }
+        Ok(relocations)
+    }
+
+    /// Decodes the action data for this action type from the given byte array, for an action
+    /// entry at the given offset.
+    pub(crate) fn decode_data(
+        &self,
+        bytes: &[u8],
+        action_offset: u32,
+        options: DecodeOptions,
+    ) -> Result<ExActionData, ExtabDecodeError> {
+        //The byte array starts after the action type and param bytes
+        let base = action_offset.wrapping_add(2);
+        self.decode_data_bytes(bytes, options.endian)
+            .map_err(|e| e.relocate(base))
+    }
+
+    fn decode_data_bytes(
+        &self,
+        bytes: &[u8],
+        endian: Endian,
+    ) -> Result<ExActionData, ExtabDecodeError> {
+        let mut offset: usize = 0;
+
+        let data = match self {
             ExAction::EndOfList => ExActionData::EndOfList {},
             ExAction::Branch => {
-                let target_offset = mem_utils::read_uint16(&self.bytes, &mut offset, true);
+                let target_offset =
+                    mem_utils::read_uint16(bytes, &mut offset, true, "target_offset", endian)?;
                 ExActionData::Branch { target_offset }
             }
             ExAction::DestroyLocal => {
-                let local_offset = mem_utils::read_uint16(&self.bytes, &mut offset, true);
-                let dtor_address = mem_utils::read_uint32(&self.bytes, &mut offset, true);
+                let local_offset =
+                    mem_utils::read_uint16(bytes, &mut offset, true, "local_offset", endian)?;
+                let dtor_address =
+                    mem_utils::read_uint32(bytes, &mut offset, true, "dtor_address", endian)?;
                 ExActionData::DestroyLocal {
                     local_offset,
                     dtor_address,
                 }
             }
             ExAction::DestroyLocalCond => {
-                let condition = mem_utils::read_uint16(&self.bytes, &mut offset, true);
-                let local_offset = mem_utils::read_uint16(&self.bytes, &mut offset, true);
-                let unk4 = mem_utils::read_uint16(&self.bytes, &mut offset, true);
-                let dtor_address = mem_utils::read_uint32(&self.bytes, &mut offset, true);
+                let condition =
+                    mem_utils::read_uint16(bytes, &mut offset, true, "condition", endian)?;
+                let local_offset =
+                    mem_utils::read_uint16(bytes, &mut offset, true, "local_offset", endian)?;
+                let unk4 = mem_utils::read_uint16(bytes, &mut offset, true, "unk4", endian)?;
+                let dtor_address =
+                    mem_utils::read_uint32(bytes, &mut offset, true, "dtor_address", endian)?;
                 ExActionData::DestroyLocalCond {
                     condition,
                     local_offset,
@@ -309,18 +752,24 @@ impl ExceptionAction {
                 }
             }
             ExAction::DestroyLocalPointer => {
-                let local_pointer = mem_utils::read_uint16(&self.bytes, &mut offset, true);
-                let dtor_address = mem_utils::read_uint32(&self.bytes, &mut offset, true);
+                let local_pointer =
+                    mem_utils::read_uint16(bytes, &mut offset, true, "local_pointer", endian)?;
+                let dtor_address =
+                    mem_utils::read_uint32(bytes, &mut offset, true, "dtor_address", endian)?;
                 ExActionData::DestroyLocalPointer {
                     local_pointer,
                     dtor_address,
                 }
             }
             ExAction::DestroyLocalArray => {
-                let local_array = mem_utils::read_uint16(&self.bytes, &mut offset, true);
-                let elements = mem_utils::read_uint16(&self.bytes, &mut offset, true);
-                let element_size = mem_utils::read_uint16(&self.bytes, &mut offset, true);
-                let dtor_address = mem_utils::read_uint32(&self.bytes, &mut offset, true);
+                let local_array =
+                    mem_utils::read_uint16(bytes, &mut offset, true, "local_array", endian)?;
+                let elements =
+                    mem_utils::read_uint16(bytes, &mut offset, true, "elements", endian)?;
+                let element_size =
+                    mem_utils::read_uint16(bytes, &mut offset, true, "element_size", endian)?;
+                let dtor_address =
+                    mem_utils::read_uint32(bytes, &mut offset, true, "dtor_address", endian)?;
                 ExActionData::DestroyLocalArray {
                     local_array,
                     elements,
@@ -329,9 +778,12 @@ impl ExceptionAction {
                 }
             }
             ExAction::DestroyBase => {
-                let object_pointer = mem_utils::read_uint16(&self.bytes, &mut offset, true);
-                let member_offset = mem_utils::read_uint32(&self.bytes, &mut offset, true);
-                let dtor_address = mem_utils::read_uint32(&self.bytes, &mut offset, true);
+                let object_pointer =
+                    mem_utils::read_uint16(bytes, &mut offset, true, "object_pointer", endian)?;
+                let member_offset =
+                    mem_utils::read_uint32(bytes, &mut offset, true, "member_offset", endian)?;
+                let dtor_address =
+                    mem_utils::read_uint32(bytes, &mut offset, true, "dtor_address", endian)?;
                 ExActionData::DestroyBase {
                     object_pointer,
                     member_offset,
@@ -339,9 +791,12 @@ impl ExceptionAction {
                 }
             }
             ExAction::DestroyMember => {
-                let object_pointer = mem_utils::read_uint16(&self.bytes, &mut offset, true);
-                let member_offset = mem_utils::read_uint32(&self.bytes, &mut offset, true);
-                let dtor_address = mem_utils::read_uint32(&self.bytes, &mut offset, true);
+                let object_pointer =
+                    mem_utils::read_uint16(bytes, &mut offset, true, "object_pointer", endian)?;
+                let member_offset =
+                    mem_utils::read_uint32(bytes, &mut offset, true, "member_offset", endian)?;
+                let dtor_address =
+                    mem_utils::read_uint32(bytes, &mut offset, true, "dtor_address", endian)?;
                 ExActionData::DestroyMember {
                     object_pointer,
                     member_offset,
@@ -349,11 +804,15 @@ impl ExceptionAction {
                 }
             }
             ExAction::DestroyMemberCond => {
-                let condition = mem_utils::read_uint16(&self.bytes, &mut offset, true);
-                let object_pointer = mem_utils::read_uint16(&self.bytes, &mut offset, true);
-                let member_offset = mem_utils::read_uint32(&self.bytes, &mut offset, true);
-                let unk8 = mem_utils::read_uint16(&self.bytes, &mut offset, true);
-                let dtor_address = mem_utils::read_uint32(&self.bytes, &mut offset, true);
+                let condition =
+                    mem_utils::read_uint16(bytes, &mut offset, true, "condition", endian)?;
+                let object_pointer =
+                    mem_utils::read_uint16(bytes, &mut offset, true, "object_pointer", endian)?;
+                let member_offset =
+                    mem_utils::read_uint32(bytes, &mut offset, true, "member_offset", endian)?;
+                let unk8 = mem_utils::read_uint16(bytes, &mut offset, true, "unk8", endian)?;
+                let dtor_address =
+                    mem_utils::read_uint32(bytes, &mut offset, true, "dtor_address", endian)?;
                 ExActionData::DestroyMemberCond {
                     condition,
                     object_pointer,
@@ -363,11 +822,16 @@ impl ExceptionAction {
                 }
             }
             ExAction::DestroyMemberArray => {
-                let object_pointer = mem_utils::read_uint16(&self.bytes, &mut offset, true);
-                let member_offset = mem_utils::read_uint32(&self.bytes, &mut offset, true);
-                let elements = mem_utils::read_uint32(&self.bytes, &mut offset, true);
-                let element_size = mem_utils::read_uint32(&self.bytes, &mut offset, true);
-                let dtor_address = mem_utils::read_uint32(&self.bytes, &mut offset, true);
+                let object_pointer =
+                    mem_utils::read_uint16(bytes, &mut offset, true, "object_pointer", endian)?;
+                let member_offset =
+                    mem_utils::read_uint32(bytes, &mut offset, true, "member_offset", endian)?;
+                let elements =
+                    mem_utils::read_uint32(bytes, &mut offset, true, "elements", endian)?;
+                let element_size =
+                    mem_utils::read_uint32(bytes, &mut offset, true, "element_size", endian)?;
+                let dtor_address =
+                    mem_utils::read_uint32(bytes, &mut offset, true, "dtor_address", endian)?;
                 ExActionData::DestroyMemberArray {
                     object_pointer,
                     member_offset,
@@ -377,18 +841,23 @@ impl ExceptionAction {
                 }
             }
             ExAction::DeletePointer => {
-                let object_pointer = mem_utils::read_uint16(&self.bytes, &mut offset, true);
-                let dtor_address = mem_utils::read_uint32(&self.bytes, &mut offset, true);
+                let object_pointer =
+                    mem_utils::read_uint16(bytes, &mut offset, true, "object_pointer", endian)?;
+                let dtor_address =
+                    mem_utils::read_uint32(bytes, &mut offset, true, "dtor_address", endian)?;
                 ExActionData::DeletePointer {
                     object_pointer,
                     dtor_address,
                 }
             }
             ExAction::DeletePointerCond => {
-                let condition = mem_utils::read_uint16(&self.bytes, &mut offset, true);
-                let object_pointer = mem_utils::read_uint16(&self.bytes, &mut offset, true);
-                let unk4 = mem_utils::read_uint16(&self.bytes, &mut offset, true);
-                let dtor_address = mem_utils::read_uint32(&self.bytes, &mut offset, true);
+                let condition =
+                    mem_utils::read_uint16(bytes, &mut offset, true, "condition", endian)?;
+                let object_pointer =
+                    mem_utils::read_uint16(bytes, &mut offset, true, "object_pointer", endian)?;
+                let unk4 = mem_utils::read_uint16(bytes, &mut offset, true, "unk4", endian)?;
+                let dtor_address =
+                    mem_utils::read_uint32(bytes, &mut offset, true, "dtor_address", endian)?;
                 ExActionData::DeletePointerCond {
                     condition,
                     object_pointer,
@@ -397,10 +866,13 @@ impl ExceptionAction {
                 }
             }
             ExAction::CatchBlock => {
-                let unk0 = mem_utils::read_uint16(&self.bytes, &mut offset, true);
-                let catch_type = mem_utils::read_uint32(&self.bytes, &mut offset, true);
-                let catch_pc_offset = mem_utils::read_uint16(&self.bytes, &mut offset, true);
-                let cinfo_ref = mem_utils::read_uint16(&self.bytes, &mut offset, true);
+                let unk0 = mem_utils::read_uint16(bytes, &mut offset, true, "unk0", endian)?;
+                let catch_type =
+                    mem_utils::read_uint32(bytes, &mut offset, true, "catch_type", endian)?;
+                let catch_pc_offset =
+                    mem_utils::read_uint16(bytes, &mut offset, true, "catch_pc_offset", endian)?;
+                let cinfo_ref =
+                    mem_utils::read_uint16(bytes, &mut offset, true, "cinfo_ref", endian)?;
                 ExActionData::CatchBlock {
                     unk0,
                     catch_type,
@@ -409,20 +881,29 @@ impl ExceptionAction {
                 }
             }
             ExAction::ActiveCatchBlock => {
-                let cinfo_ref = mem_utils::read_uint16(&self.bytes, &mut offset, true);
+                let cinfo_ref =
+                    mem_utils::read_uint16(bytes, &mut offset, true, "cinfo_ref", endian)?;
                 ExActionData::ActiveCatchBlock { cinfo_ref }
             }
             ExAction::Terminate => ExActionData::Terminate {},
             ExAction::Specification => {
-                let specs = mem_utils::read_uint16(&self.bytes, &mut offset, true);
-                let pc_offset = mem_utils::read_uint32(&self.bytes, &mut offset, true);
-                let cinfo_ref = mem_utils::read_uint32(&self.bytes, &mut offset, true);
+                let specs = mem_utils::read_uint16(bytes, &mut offset, true, "specs", endian)?;
+                let pc_offset =
+                    mem_utils::read_uint32(bytes, &mut offset, true, "pc_offset", endian)?;
+                let cinfo_ref =
+                    mem_utils::read_uint32(bytes, &mut offset, true, "cinfo_ref", endian)?;
 
                 //Read the specified number of 32 bit values and add them to the list
-                let length = specs as i32;
-                let mut spec: Vec<u32> = vec![];
+                let length = specs as usize;
+                let mut spec: Vec<u32> = Vec::with_capacity(length);
                 for _i in 0..length {
-                    spec.push(mem_utils::read_uint32(&self.bytes, &mut offset, true));
+                    spec.push(mem_utils::read_uint32(
+                        bytes,
+                        &mut offset,
+                        true,
+                        "spec",
+                        endian,
+                    )?);
                 }
                 ExActionData::Specification {
                     specs,
@@ -432,10 +913,13 @@ impl ExceptionAction {
                 }
             }
             ExAction::CatchBlock32 => {
-                let unk0 = mem_utils::read_uint16(&self.bytes, &mut offset, true);
-                let catch_type = mem_utils::read_uint32(&self.bytes, &mut offset, true);
-                let catch_pc_offset = mem_utils::read_uint32(&self.bytes, &mut offset, true);
-                let cinfo_ref = mem_utils::read_uint32(&self.bytes, &mut offset, true);
+                let unk0 = mem_utils::read_uint16(bytes, &mut offset, true, "unk0", endian)?;
+                let catch_type =
+                    mem_utils::read_uint32(bytes, &mut offset, true, "catch_type", endian)?;
+                let catch_pc_offset =
+                    mem_utils::read_uint32(bytes, &mut offset, true, "catch_pc_offset", endian)?;
+                let cinfo_ref =
+                    mem_utils::read_uint32(bytes, &mut offset, true, "cinfo_ref", endian)?;
                 ExActionData::CatchBlock32 {
                     unk0,
                     catch_type,
@@ -443,8 +927,83 @@ impl ExceptionAction {
                     cinfo_ref,
                 }
             }
+        };
+        Ok(data)
+    }
+}
+
+/// Struct for exception actions.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExceptionAction {
+    //General values
+    pub action_offset: u32,
+    pub action_type: ExAction, //0x0
+    pub action_param: u8,      //0x1
+    pub has_end_bit: bool,     //true if action type byte has bit 7 set (type & 0x80)
+    pub bytes: Vec<u8>,
+}
+
+impl ExceptionAction {
+    pub fn new() -> Self {
+        Self {
+            action_offset: 0,
+            action_type: ExAction::EndOfList,
+            action_param: 0,
+            has_end_bit: false,
+            bytes: vec![],
         }
     }
+
+    /// Returns whether this action has a destuctor reference or not.
+    pub fn has_dtor_ref(&self) -> bool {
+        self.action_type.has_dtor_ref()
+    }
+
+    /// Returns the relocation data for the dtor function in this action entry, if any.
+    pub fn get_dtor_relocation(&self) -> Option<(u32, u32)> {
+        self.get_dtor_relocation_with_options(DecodeOptions::default())
+    }
+
+    /// Returns the relocation data for the dtor function, reading the action data with the
+    /// byte order of the table. See `get_dtor_relocation`.
+    pub fn get_dtor_relocation_with_options(&self, options: DecodeOptions) -> Option<(u32, u32)> {
+        self.action_type.get_dtor_relocation(&self.bytes, options)
+    }
+
+    /// Returns the relocations for the dtor and type info addresses in this action entry,
+    /// with offsets relative to the start of the table.
+    pub fn get_relocations(&self) -> Result<Vec<Relocation>, ExtabDecodeError> {
+        self.get_relocations_with_options(DecodeOptions::default())
+    }
+
+    /// Returns the relocations for the dtor and type info addresses, reading the action data
+    /// with the byte order of the table. See `get_relocations`.
+    pub fn get_relocations_with_options(
+        &self,
+        options: DecodeOptions,
+    ) -> Result<Vec<Relocation>, ExtabDecodeError> {
+        self.action_type
+            .get_relocations(&self.bytes, self.action_offset, options)
+    }
+
+    /// Decodes the action data from the byte array depending on the set action type, and converts it
+    /// to an ExActionData enum containing the decoded data.
+    ///
+    /// Returns an error if the byte array is too short for the action type. Error offsets are
+    /// relative to the start of the table, based on the action offset.
+    pub fn get_exaction_data(&self) -> Result<ExActionData, ExtabDecodeError> {
+        self.get_exaction_data_with_options(DecodeOptions::default())
+    }
+
+    /// Decodes the action data with the byte order of the table. See `get_exaction_data`.
+    pub fn get_exaction_data_with_options(
+        &self,
+        options: DecodeOptions,
+    ) -> Result<ExActionData, ExtabDecodeError> {
+        self.action_type
+            .decode_data(&self.bytes, self.action_offset, options)
+    }
 }
 
 impl Default for ExceptionAction {
@@ -455,6 +1014,7 @@ impl Default for ExceptionAction {
 
 /// Struct for pc actions.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PCAction {
     pub start_pc: u32,
     pub end_pc: u32,
@@ -477,26 +1037,84 @@ impl Default for PCAction {
     }
 }
 
-/// Struct for exception table relocation (always dtor function address)
-#[derive(Debug, Clone)]
+/// Enum for the relocation types used in exception tables.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RelocationKind {
+    /// R_PPC_ADDR32, a 32 bit absolute address
+    Addr32,
+}
+
+impl RelocationKind {
+    /// Returns the ELF relocation type value.
+    pub fn elf_type(&self) -> u32 {
+        match self {
+            RelocationKind::Addr32 => 1,
+        }
+    }
+}
+
+/// Enum for the action fields that hold relocated addresses.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RelocationField {
+    /// Dtor function address of a destroy or delete action
+    Dtor,
+    /// Type info address of a catch block
+    CatchType,
+    /// Type info address in the type list of a specification
+    SpecType,
+}
+
+/// Struct for the target of a relocation in a relocatable module.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ModuleTarget {
+    /// ID of the target module, 0 for the main executable
+    pub module_id: u32,
+    /// Index of the target section, 0 for absolute addresses in the main executable
+    pub section: u8,
+    /// Offset in the target section, or the address for the main executable
+    pub offset: u32,
+}
+
+/// Struct for exception table relocations (dtor function and type info addresses)
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Relocation {
     pub offset: u32,
+    /// Address stored in the table data
     pub address: u32,
+    pub kind: RelocationKind,
+    pub field: RelocationField,
+    /// Name of the target symbol, if known
+    pub symbol: Option<String>,
+    pub addend: i32,
+    /// Target in a relocatable module, if the table was read from one
+    pub target: Option<ModuleTarget>,
+}
+
+impl Relocation {
+    /// Creates a relocation without a symbol.
+    pub fn new(offset: u32, address: u32, field: RelocationField) -> Self {
+        Self {
+            offset,
+            address,
+            kind: RelocationKind::Addr32,
+            field,
+            symbol: None,
+            addend: 0,
+            target: None,
+        }
+    }
 }
 
 /// Struct containing all the data from the decoded exception table.
 #[derive(Debug, Clone)]
 pub struct ExceptionTableData {
-    pub flag_val: u16, //0x0-1
-    //Flag bits (16 bit value)
-    pub has_elf_vector: bool,    //bit 1
-    pub large_frame: bool,       //bit 3
-    pub has_frame_pointer: bool, //bit 4
-    pub saved_cr: bool,          //bit 5
-    pub fpr_save_range: u32,     //bits 6-10
-    pub gpr_save_range: u32,     //bits 11-15
-
-    pub et_field: u16, //0x2-3
+    /// Byte order and target the table was decoded with
+    pub options: DecodeOptions,
+    pub flags: ExtabFlags, //0x0-3
 
     pub pc_actions: Vec<PCAction>,
     pub exception_actions: Vec<ExceptionAction>,
@@ -506,494 +1124,480 @@ pub struct ExceptionTableData {
 impl ExceptionTableData {
     fn new() -> Self {
         Self {
-            flag_val: 0,
-            has_elf_vector: false,
-            large_frame: false,
-            has_frame_pointer: false,
-            saved_cr: false,
-            fpr_save_range: 0,
-            gpr_save_range: 0,
-            et_field: 0,
+            options: DecodeOptions::default(),
+            flags: ExtabFlags::default(),
             pc_actions: vec![],
             exception_actions: vec![],
             relocations: vec![],
         }
     }
 
-    fn calculate_flag_values(&mut self) {
-        self.has_elf_vector = ((self.flag_val >> 1) & 1) == 1;
-        self.large_frame = ((self.flag_val >> 3) & 1) == 1;
-        self.has_frame_pointer = ((self.flag_val >> 4) & 1) == 1;
-        self.saved_cr = ((self.flag_val >> 5) & 1) == 1;
-        self.fpr_save_range = ((self.flag_val >> 6) & 0b11111) as u32;
-        self.gpr_save_range = ((self.flag_val >> 11) & 0b11111) as u32;
+    /// Returns the register frame offsets in this table's actions are relative to.
+    pub fn frame_base(&self) -> FrameBase {
+        if self.flags.has_frame_pointer() {
+            FrameBase::Fp
+        } else {
+            FrameBase::Sp
+        }
     }
 
-    /// Converts the table into a string, taking in an array of the function
-    /// names required for the table.
-    ///
-    /// Returns 'None' if an error occurs.
-    pub fn to_string(&self, func_names: Vec<String>) -> Option<String> {
-        let mut sb = String::from("");
+    /// Returns the name of every relocation target from the given resolver, in the order of
+    /// the relocations.
+    pub fn relocation_names<R: SymbolResolver>(&self, symbols: R) -> Vec<Option<String>> {
+        let mut counts = [0usize; 3];
+        self.relocations
+            .iter()
+            .map(|relocation| {
+                let count = &mut counts[relocation.field as usize];
+                let name = symbols.resolve_relocation(*count, relocation);
+                *count += 1;
+                name
+            })
+            .collect()
+    }
 
-        sb += "Flag values:\n";
-        sb += format!(
-            "{}",
-            format_args!(
-                "Has Elf Vector: {}\n",
-                if self.has_elf_vector { "Yes" } else { "No" }
-            )
-        )
-        .as_str();
-        sb += format!(
-            "{}",
-            format_args!(
-                "Large Frame: {}\n",
-                if self.large_frame { "Yes" } else { "No" }
-            )
-        )
-        .as_str();
-        sb += format!(
-            "{}",
-            format_args!(
-                "Has Frame Pointer: {}\n",
-                if self.has_frame_pointer { "Yes" } else { "No" }
-            )
-        )
-        .as_str();
-        sb += format!(
-            "{}",
-            format_args!("Saved CR: {}\n", if self.saved_cr { "Yes" } else { "No" })
-        )
-        .as_str();
-
-        if self.fpr_save_range != 0 {
-            let start_fpr = 31 - (self.fpr_save_range - 1);
-            let fpr_string: String = if start_fpr == 31 {
-                String::from("fp31")
-            } else {
-                format!("fp{start_fpr}-fp31")
-            };
-            sb += format!("Saved FPR range: {fpr_string}\n").as_str();
-        }
-        if self.gpr_save_range != 0 {
-            let start_gpr = 31 - (self.gpr_save_range - 1);
-            let gpr_string: String = if start_gpr == 31 {
-                String::from("r31")
-            } else {
-                format!("r{start_gpr}-r31")
-            };
-            sb += format!("Saved GPR range: {gpr_string}\n").as_str();
-        }
-        sb += "\n";
-
-        let num_pcactions = self.pc_actions.len();
-
-        //Print exception range entries
-        if num_pcactions > 0 {
-            sb += "PC actions:\n";
-            for i in 0..num_pcactions {
-                let action = &self.pc_actions[i];
-                let start_pc = action.start_pc;
-                let end_pc = action.end_pc;
-                let action_offset = action.action_offset;
-                if start_pc != end_pc {
-                    sb += format!("PC={start_pc:08X}:{end_pc:08X}, Action: {action_offset:06X}\n")
-                        .as_str();
-                } else {
-                    sb += format!("PC={start_pc:08X}, Action: {action_offset:06X}\n").as_str();
-                }
+    /// Sets the symbol of every relocation that has none using the given resolver.
+    pub fn resolve_symbols<R: SymbolResolver>(&mut self, symbols: R) {
+        let names = self.relocation_names(symbols);
+        for (relocation, name) in self.relocations.iter_mut().zip(names) {
+            if relocation.symbol.is_none() {
+                relocation.symbol = name;
             }
-
-            sb += "\n";
         }
+    }
 
-        let num_exactions = self.exception_actions.len();
-
-        if num_exactions > 0 {
-            sb += "Exception actions:\n";
-            let local_reg_string = if self.has_frame_pointer { "FP" } else { "SP" };
-            let mut func_index: usize = 0;
-
-            for i in 0..num_exactions {
-                let action = &self.exception_actions[i];
-                let mut line = String::from("");
-                let action_offset = action.action_offset;
-                let action_name = action.action_type.convert_to_string();
-                line += format!("{action_offset:06X}:\nType: {action_name}\n").as_str();
-
-                let has_dtor_ref = action.has_dtor_ref();
-                let exaction_data = action.get_exaction_data();
-
-                match exaction_data {
-                    ExActionData::EndOfList => {}
-                    ExActionData::Branch { target_offset } => {
-                        line += format!("Action: {target_offset:06X}\n").as_str();
-                    }
-                    ExActionData::DestroyLocal { local_offset, .. } => {
-                        line += format!("Local: {local_offset:#X}({local_reg_string})\n").as_str();
-                    }
-                    ExActionData::DestroyLocalCond {
-                        condition,
-                        local_offset,
-                        ..
-                    } => {
-                        line += format!("Local: {local_offset:#X}({local_reg_string})\n").as_str();
-
-                        //The action param is used to determine the type of reference for the condition (0: local offset, 1: register)
-                        if action.action_param == 0 {
-                            //Local offset
-                            line += format!("Cond: {condition:#X}({local_reg_string})\n").as_str();
-                        } else {
-                            //Register
-                            //In this case, the local offset param is actually the register number
-                            line += format!("Cond: r{condition}\n").as_str();
-                        }
-                    }
-                    ExActionData::DestroyLocalPointer { local_pointer, .. } => {
-                        let mode = action.action_param >> 7;
-                        if mode == 0 {
-                            //Local offset
-                            line +=
-                                format!("Pointer: {local_pointer:#X}({local_reg_string})\n").as_str();
-                        } else {
-                            //Register
-                            line += format!("Pointer: r{local_pointer}\n").as_str();
-                        }
-                    }
-                    ExActionData::DestroyLocalArray {
-                        local_array,
-                        elements,
-                        element_size,
-                        ..
-                    } => {
-                        line += format!("Array: {local_array:#X}({local_reg_string})\nElements: {elements}\nSize: {element_size}\n").as_str();
-                    }
-                    ExActionData::DestroyBase {
-                        object_pointer,
-                        member_offset,
-                        ..
-                    } => {
-                        let mode = action.action_param >> 7;
-                        if mode == 0 {
-                            line += format!("Member: {object_pointer:#X}({local_reg_string})+{member_offset:#X}\n").as_str();
-                        } else {
-                            line +=
-                                format!("Member: {member_offset:#X}(r{object_pointer})\n").as_str();
-                        }
-                    }
-                    ExActionData::DestroyMember {
-                        object_pointer,
-                        member_offset,
-                        ..
-                    } => {
-                        let mode = action.action_param >> 7;
-                        if mode == 0 {
-                            line += format!("Member: {object_pointer:#X}({local_reg_string})+{member_offset:#X}\n").as_str();
-                        } else {
-                            line +=
-                                format!("Member: {member_offset:#X}(r{object_pointer})\n").as_str();
-                        }
-                    }
-                    ExActionData::DestroyMemberCond {
-                        condition,
-                        object_pointer,
-                        member_offset,
-                        ..
-                    } => {
-                        let mode = (action.action_param >> 6) & 1;
-                        if mode == 0 {
-                            line += format!("Member: {object_pointer:#X}({local_reg_string})+{member_offset:#X}\n").as_str();
-                        } else {
-                            //Register
-                            line +=
-                                format!("Member: {member_offset:#X}(r{object_pointer})\n").as_str();
-                        }
-                        let condition_mode = action.action_param >> 7;
-                        if condition_mode == 0 {
-                            //Local offset
-                            line += format!("Cond: {condition:#X}({local_reg_string})\n").as_str();
-                        } else {
-                            //Register
-                            line += format!("Cond: r{condition}\n").as_str();
-                        }
-                    }
-                    ExActionData::DestroyMemberArray {
-                        object_pointer,
-                        member_offset,
-                        elements,
-                        element_size,
-                        ..
-                    } => {
-                        let mode = action.action_param >> 7;
-                        if mode == 0 {
-                            //Local offset
-                            line += format!(
-                                "Member: {object_pointer:#X}({local_reg_string})+0x{member_offset}\n"
-                            )
-                            .as_str();
-                        } else {
-                            //Register
-                            line +=
-                                format!("Member: {member_offset:#X}(r{object_pointer})\n").as_str();
-                        }
-                        line += format!("Elements: {elements}\nSize: {element_size}\n").as_str();
-                    }
-                    ExActionData::DeletePointer { object_pointer, .. } => {
-                        let mode = action.action_param >> 7;
-                        if mode == 0 {
-                            //Local offset
-                            line += format!("Pointer: {object_pointer:#X}({local_reg_string})\n")
-                                .as_str();
-                        } else {
-                            //Register
-                            line += format!("Pointer: r{object_pointer})\n").as_str();
-                        }
-                    }
-                    ExActionData::DeletePointerCond {
-                        condition,
-                        object_pointer,
-                        ..
-                    } => {
-                        let mode = (action.action_param >> 6) & 1;
-                        if mode == 0 {
-                            //Local offset
-                            line += format!("Pointer: {object_pointer:#X}({local_reg_string})\n")
-                                .as_str();
-                        } else {
-                            //Register
-                            line += format!("Pointer: r{object_pointer})\n").as_str();
-                        }
-                        let condition_mode = action.action_param >> 7;
-                        if condition_mode == 0 {
-                            //Local offset
-                            line += format!("Cond: {condition:#X}({local_reg_string})\n").as_str();
-                        } else {
-                            //Register
-                            line += format!("Cond: r{condition}\n").as_str();
-                        }
-                    }
-                    ExActionData::CatchBlock {
-                        catch_type,
-                        catch_pc_offset,
-                        cinfo_ref,
-                        ..
-                    } => {
-                        line += format!("Local: {cinfo_ref:#X}({local_reg_string})\nPC: {catch_pc_offset:08X}\ncatch_type_addr: {catch_type:08X}\n").as_str();
-                    }
-                    ExActionData::ActiveCatchBlock { cinfo_ref } => {
-                        line += format!("Local: {cinfo_ref:#X}({local_reg_string})\n").as_str();
-                    }
-                    ExActionData::Terminate => {}
-                    ExActionData::Specification {
-                        specs,
-                        pc_offset,
-                        cinfo_ref,
-                        ..
-                    } => {
-                        line += format!("Local: {cinfo_ref:#X}({local_reg_string})\nPC: {pc_offset:08X}\nTypes: {specs}\n").as_str();
-                    }
-                    ExActionData::CatchBlock32 {
-                        catch_type,
-                        catch_pc_offset,
-                        cinfo_ref,
-                        ..
-                    } => {
-                        line += format!("Local: {cinfo_ref:#X}({local_reg_string})\nPC: {catch_pc_offset:08X}\ncatch_type_addr: {catch_type:08X}\n").as_str();
-                    }
-                }
+    /// Returns the relocations of the given field within the action.
+    fn action_relocations<'a>(
+        &'a self,
+        action: &ExceptionAction,
+        field: RelocationField,
+    ) -> impl Iterator<Item = &'a Relocation> {
+        let range = action_range(action);
+        self.relocations.iter().filter(move |relocation| {
+            relocation.field == field
+                && range
+                    .as_ref()
+                    .map_or(false, |range| range.contains(&relocation.offset))
+        })
+    }
 
-                //If the action references a dtor, print it out using the name array
-                if has_dtor_ref {
-                    if func_index >= func_names.len() {
-                        line += "Error: Invalid function array index\n";
-                    } else {
-                        let func_name = func_names[func_index].as_str();
-                        line += format!("Dtor: \"{func_name}\"\n").as_str();
-                        func_index += 1;
-                    }
-                }
+    /// Returns the name of the relocation of the given field within the action from the
+    /// given resolver, or its address if the resolver has no name for it.
+    fn action_symbol<R: SymbolResolver>(
+        &self,
+        action: &ExceptionAction,
+        field: RelocationField,
+        symbols: &R,
+    ) -> Option<Result<String, u32>> {
+        let range = action_range(action)?;
+        //Resolvers number the relocations of each field separately, like `relocation_names`
+        self.relocations
+            .iter()
+            .filter(|relocation| relocation.field == field)
+            .enumerate()
+            .find(|(_, relocation)| range.contains(&relocation.offset))
+            .map(|(index, relocation)| {
+                symbols
+                    .resolve_relocation(index, relocation)
+                    .ok_or(relocation.address)
+            })
+    }
 
-                if action.has_end_bit {
-                    line += "Has end bit\n"
-                };
-                sb += line.as_str(); //Print the line
-            }
-        }
+    /// Returns a renderer that writes the table as text, naming the dtors and catch types
+    /// with the given resolver. A list of names is matched to the dtors in order.
+    pub fn render<R: SymbolResolver>(&self, symbols: R) -> Renderer<'_, R> {
+        Renderer::new(self, symbols)
+    }
 
-        Some(sb)
+    /// Returns a renderer that writes the table as text using the given format options.
+    pub fn render_with_options<R: SymbolResolver>(
+        &self,
+        symbols: R,
+        options: FormatOptions,
+    ) -> Renderer<'_, R> {
+        Renderer::with_options(self, symbols, options)
     }
 }
 
-struct ExtabDecoder {
+/// Returns the range of table offsets the action covers, or 'None' if the end of the action
+/// is past the largest offset.
+fn action_range(action: &ExceptionAction) -> Option<core::ops::Range<u32>> {
+    let size = u32::try_from(action.bytes.len()).ok()?.checked_add(2)?;
+    Some(action.action_offset..action.action_offset.checked_add(size)?)
+}
+
+/// Struct for a problem found while decoding a table in lenient mode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Offset of the table entry that caused the problem.
+    pub offset: u32,
+    pub error: ExtabDecodeError,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{:X}: {}", self.offset, self.error)
+    }
+}
+
+struct ExtabDecoder<'a> {
     extab_data: ExceptionTableData,
-    offset: i32,
-    data: Vec<u8>,
-    length: i32,
+    offset: usize,
+    data: &'a [u8],
+    length: usize,
+    //If true, recoverable errors are recorded in diagnostics instead of stopping the decoding
+    lenient: bool,
+    diagnostics: Vec<Diagnostic>,
+    entry_offset: usize, //Offset of the entry currently being parsed
 }
 
-impl ExtabDecoder {
-    fn new() -> Self {
+impl<'a> ExtabDecoder<'a> {
+    fn new(data: &'a [u8], options: DecodeOptions) -> Self {
+        let mut extab_data = ExceptionTableData::new();
+        extab_data.options = options;
         Self {
-            extab_data: ExceptionTableData::new(),
+            extab_data,
             offset: 0,
-            data: vec![],
-            length: 0,
+            data,
+            length: data.len(),
+            lenient: false,
+            diagnostics: vec![],
+            entry_offset: 0,
+        }
+    }
+
+    /// Returns the given error in strict mode. In lenient mode, the error is recorded
+    /// as a diagnostic for the current entry instead.
+    fn report(&mut self, error: ExtabDecodeError) -> Result<(), ExtabDecodeError> {
+        if !self.lenient {
+            return Err(error);
         }
+        self.diagnostics.push(Diagnostic {
+            offset: self.entry_offset as u32,
+            error,
+        });
+        Ok(())
     }
 
-    fn parse_exception_table(&mut self, bytes: &[u8]) -> Result<(), ExtabDecodeError> {
+    /// Returns the lowest action offset after the given offset that is known to start an
+    /// action entry, either from a PC action or from a branch action.
+    fn next_known_action_offset(&self, after: usize) -> Option<usize> {
+        let pc_targets = self.extab_data.pc_actions.iter().map(|a| a.action_offset);
+        let branch_targets = self.extab_data.exception_actions.iter().filter_map(|a| {
+            match a.get_exaction_data_with_options(self.extab_data.options) {
+                Ok(ExActionData::Branch { target_offset }) => Some(target_offset as u32),
+                _ => None,
+            }
+        });
+        pc_targets
+            .chain(branch_targets)
+            .map(|offset| offset as usize)
+            .filter(|&offset| offset > after && offset < self.length)
+            .min()
+    }
+
+    fn parse_exception_table(&mut self) -> Result<(), ExtabDecodeError> {
         self.offset = 0;
-        self.data = Vec::from(bytes);
-        self.length = self.data.len() as i32;
+        let endian = self.extab_data.options.endian;
 
         //If the array is empty, return an error.
         if self.length < 8 {
-            return Err(ExtabDecodeError::ArrayTooSmall(self.length as u32));
+            self.report(ExtabDecodeError::ArrayTooSmall {
+                length: self.length as u32,
+                location: ErrorLocation::new(0, ""),
+            })?;
+            return Ok(());
         }
 
         //Parse the header flag value
-        self.extab_data.flag_val = mem_utils::read_uint16(&self.data, &mut self.offset, true);
-        self.extab_data.calculate_flag_values();
-        self.extab_data.et_field = mem_utils::read_uint16(&self.data, &mut self.offset, true);
+        let flag_val = mem_utils::read_uint16(self.data, &mut self.offset, true, "flags", endian)?;
+        let et_field =
+            mem_utils::read_uint16(self.data, &mut self.offset, true, "et_field", endian)?;
+        self.extab_data.flags = ExtabFlags::from_raw(flag_val, et_field);
 
         //Check whether the table is 8 bytes but the terminator isn't zero. If so,
         //throw an error.
-        let terminator = mem_utils::read_uint32(&self.data, &mut self.offset, false);
+        let terminator =
+            mem_utils::read_uint32(self.data, &mut self.offset, false, "terminator", endian)?;
         if self.length == 8 && terminator != 0 {
-            return Err(ExtabDecodeError::InvalidSmallTableTerminator);
+            self.report(ExtabDecodeError::InvalidSmallTableTerminator {
+                location: ErrorLocation::new(4, "terminator"),
+            })?;
+            return Ok(());
         }
 
         //Parse range entries until we hit the terminator (32 bit zero value)
-        while mem_utils::read_uint32(&self.data, &mut self.offset, false) != 0 {
-            let mut pcaction = PCAction::new();
-            pcaction.start_pc = mem_utils::read_uint32(&self.data, &mut self.offset, true);
-            let range_size: u32 =
-                (mem_utils::read_uint16(&self.data, &mut self.offset, true) as u32) * 4; //range size is encoded as size >> 2
-            pcaction.end_pc = pcaction.start_pc + range_size;
-            pcaction.action_offset =
-                mem_utils::read_uint16(&self.data, &mut self.offset, true) as u32;
-            self.extab_data.pc_actions.push(pcaction);
+        //If the data runs out before the terminator is found, the table is invalid.
+        loop {
+            self.entry_offset = self.offset;
+            let index = self.extab_data.pc_actions.len();
+            let pcaction = view::read_pc_action(self.data, &mut self.offset, endian)
+                .map_err(|e| e.with_pc_action_index(index))?;
+            match pcaction {
+                Some(pcaction) => self.extab_data.pc_actions.push(pcaction),
+                None => break,
+            }
         }
 
         self.offset += 4; //Skip the terminator
 
         //If there are still bytes remaining, there are action entries to process
+        //Index of the entry in the table, including entries skipped in lenient mode
+        let mut index: usize = 0;
         while self.offset < self.length {
-            //Console.WriteLine("Offset: " + offset);
-            self.parse_action_entry()?;
+            self.entry_offset = self.offset;
+            let result = self.parse_action_entry();
+            index += 1;
+            if let Err(error) = result.map_err(|e| e.with_action_index(index - 1)) {
+                let action_offset = self.entry_offset;
+                self.report(error)?;
+                //Since the size of a bad entry can't be trusted, skip to the next action
+                //that is known to be referenced.
+                match self.next_known_action_offset(action_offset) {
+                    Some(offset) => self.offset = offset,
+                    None => break,
+                }
+            }
         }
 
         Ok(())
     }
 
     fn parse_action_entry(&mut self) -> Result<(), ExtabDecodeError> {
-        let mut exaction = ExceptionAction::new();
-        exaction.action_offset = self.offset as u32;
-        let action_type_byte = mem_utils::read_byte(&self.data, &mut self.offset, true);
-        exaction.has_end_bit = (action_type_byte & 0x80) != 0;
-        let action_type_value: u32 = (action_type_byte & 0x7F) as u32;
-        let result = ExAction::from_int(action_type_value as i32);
-        exaction.action_type = match result {
-            Some(action) => action,
-            None => {
-                return Err(ExtabDecodeError::InvalidActionValue(
-                    action_type_value,
-                    exaction.action_offset,
-                ))
-            }
-        };
-        exaction.action_param = mem_utils::read_byte(&self.data, &mut self.offset, true);
+        let options = self.extab_data.options;
+        let exaction = ExceptionActionRef::read(self.data, &mut self.offset, options)?;
 
-        //Since the way action data is stored is too varied, we just store the remaining data as a byte
-        //array to be used later.
-        let mut size: i32;
+        //Get the relocation information for the dtor and type info references in the action,
+        //and add it to the list. The action data was already checked to be long enough.
+        let relocations = exaction
+            .get_relocations_with_options(options)
+            .map_err(|e| ExtabDecodeError::Internal {
+                location: *e.location(),
+            })?;
+        self.extab_data.relocations.extend(relocations);
 
-        match exaction.action_type {
-            ExAction::EndOfList => {
-                size = 0;
-            }
-            ExAction::Branch => {
-                size = 2;
-            }
-            ExAction::DestroyLocal => {
-                size = 6;
-            }
-            ExAction::DestroyLocalCond => {
-                size = 10;
-            }
-            ExAction::DestroyLocalPointer => {
-                size = 6;
-            }
-            ExAction::DestroyLocalArray => {
-                size = 10;
-            }
-            ExAction::DestroyBase | ExAction::DestroyMember => {
-                size = 10;
-            }
-            ExAction::DestroyMemberCond => {
-                size = 14;
-            }
-            ExAction::DestroyMemberArray => {
-                size = 18;
-            }
-            ExAction::DeletePointer => {
-                size = 6;
-            }
-            ExAction::DeletePointerCond => {
-                size = 10;
-            }
-            ExAction::CatchBlock => {
-                size = 10;
-            }
-            ExAction::ActiveCatchBlock => {
-                size = 2;
-            }
-            ExAction::Terminate => {
-                size = 0;
-            }
-            ExAction::Specification => {
-                size = 10;
-                //Calculate the length of the array, and add it to the base size
-                let length = mem_utils::read_uint16(&self.data, &mut self.offset, false) as i32;
-                size += length * 4;
-            }
-            ExAction::CatchBlock32 => {
-                size = 14;
-            }
-        }
-
-        let start_index = self.offset as usize;
-        let end_index = (self.offset + size) as usize;
-        exaction.bytes = self.data[start_index..end_index].into();
-        self.offset += size;
-
-        //Check if the action entry has a dtor reference. If so, get the relocation information from it,
-        //and add it to the list.
-        if exaction.has_dtor_ref() {
-            let (offset, addr) = match exaction.get_dtor_relocation() {
-                Some(val) => val,
-                None => {
-                    //If None was returned even though the action should have a reference, return an error
-                    return Err(ExtabDecodeError::Internal);
-                }
-            };
-
-            let reloc_offset: u32 = (start_index as u32) + offset;
-            let reloc = Relocation { offset: reloc_offset, address: addr };
-            self.extab_data.relocations.push(reloc);
-        }
-
-        self.extab_data.exception_actions.push(exaction);
+        self.extab_data.exception_actions.push(exaction.into());
         Ok(())
     }
 }
 
 /// Decodes the provided exception table data.
 ///
-/// Returns 'None' if the table is not valid.
+/// Returns an error if the table is not valid or is truncated. This function never panics,
+/// regardless of the input.
 pub fn decode_extab(data: &[u8]) -> Result<ExceptionTableData, ExtabDecodeError> {
-    let mut decoder = ExtabDecoder::new();
-    decoder.parse_exception_table(data)?;
+    decode_extab_with_options(data, DecodeOptions::default())
+}
+
+/// Decodes the provided exception table data with the given byte order and target.
+/// See `decode_extab`.
+pub fn decode_extab_with_options(
+    data: &[u8],
+    options: DecodeOptions,
+) -> Result<ExceptionTableData, ExtabDecodeError> {
+    let mut decoder = ExtabDecoder::new(data, options);
+    decoder.parse_exception_table()?;
     Ok(decoder.extab_data)
 }
+
+/// Decodes the provided exception table data, keeping everything that could be decoded.
+///
+/// Instead of failing on the first problem, every problem found is returned as a diagnostic
+/// alongside the partially decoded table. Bad action entries are left out of the table, and
+/// decoding continues at the next action referenced by a PC action or branch, if any.
+pub fn decode_extab_lenient(data: &[u8]) -> (ExceptionTableData, Vec<Diagnostic>) {
+    decode_extab_lenient_with_options(data, DecodeOptions::default())
+}
+
+/// Decodes the provided exception table data in lenient mode with the given byte order and
+/// target. See `decode_extab_lenient`.
+pub fn decode_extab_lenient_with_options(
+    data: &[u8],
+    options: DecodeOptions,
+) -> (ExceptionTableData, Vec<Diagnostic>) {
+    let mut decoder = ExtabDecoder::new(data, options);
+    decoder.lenient = true;
+    if let Err(error) = decoder.parse_exception_table() {
+        //Errors that can't be recovered from end the decoding
+        decoder.diagnostics.push(Diagnostic {
+            offset: decoder.entry_offset as u32,
+            error,
+        });
+    }
+    (decoder.extab_data, decoder.diagnostics)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Table with one PC range and a DestroyLocal action with the end bit.
+    const TABLE: [u8; 24] = [
+        0x00, 0x00, 0x00, 0x00, //Header
+        0x00, 0x00, 0x00, 0x10, 0x00, 0x04, 0x00, 0x10, //PC action
+        0x00, 0x00, 0x00, 0x00, //Terminator
+        0x82, 0x00, 0x00, 0x08, 0x80, 0x00, 0x10, 0x00, //DestroyLocal
+    ];
+
+    #[test]
+    fn decodes_table() {
+        let table = decode_extab(&TABLE).unwrap();
+        assert_eq!(table.pc_actions.len(), 1);
+        assert_eq!(table.pc_actions[0].end_pc, 0x20);
+        assert_eq!(
+            table.exception_actions[0].get_exaction_data(),
+            Ok(ExActionData::DestroyLocal {
+                local_offset: 0x8,
+                dtor_address: 0x80001000,
+            })
+        );
+        assert_eq!(table.relocations[0].offset, 0x14);
+    }
+
+    #[test]
+    fn rejects_truncated_tables() {
+        //A table cut right after the terminator is valid, it just has no actions
+        for length in (0..TABLE.len()).filter(|&length| length != 16) {
+            assert!(decode_extab(&TABLE[..length]).is_err(), "{length}");
+        }
+        assert!(matches!(
+            decode_extab(&TABLE[..7]),
+            Err(ExtabDecodeError::ArrayTooSmall { length: 7, .. })
+        ));
+        assert!(matches!(
+            decode_extab(&TABLE[..14]),
+            Err(ExtabDecodeError::MissingPcTerminator { .. })
+        ));
+        let error = decode_extab(&TABLE[..22]).unwrap_err();
+        assert_eq!(
+            error,
+            ExtabDecodeError::UnexpectedEof {
+                needed: 4,
+                available: 2,
+                location: ErrorLocation {
+                    offset: 0x14,
+                    pc_action_index: None,
+                    action_index: Some(0),
+                    field: "dtor_address",
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_oversized_values() {
+        //Specification with 0xFFFF types but only one in the data
+        let mut data = TABLE[..16].to_vec();
+        data.extend_from_slice(&[0x8F, 0x00, 0xFF, 0xFF, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert!(matches!(
+            decode_extab(&data),
+            Err(ExtabDecodeError::UnexpectedEof { .. })
+        ));
+
+        //Action type past the last known one
+        let mut data = TABLE;
+        data[16] = 0xFF;
+        assert!(matches!(
+            decode_extab(&data),
+            Err(ExtabDecodeError::InvalidActionValue { value: 0x7F, .. })
+        ));
+
+        //PC ranges that run past the end of the address space wrap around
+        let mut data = TABLE;
+        data[4..10].copy_from_slice(&[0xFF, 0xFF, 0xFF, 0xFC, 0xFF, 0xFF]);
+        let table = decode_extab(&data).unwrap();
+        assert_eq!(table.pc_actions[0].end_pc, 0x3FFF8);
+
+        //PC actions that never end
+        assert!(matches!(
+            decode_extab(&[0x01; 0x1004]),
+            Err(ExtabDecodeError::MissingPcTerminator { .. })
+        ));
+    }
+
+    #[test]
+    fn action_at_end_of_offsets() {
+        let mut table = decode_extab(&TABLE).unwrap();
+        let action = &mut table.exception_actions[0];
+        action.action_offset = u32::MAX - 2;
+        let action = action.clone();
+        table.relocations[0].offset = u32::MAX;
+        assert_eq!(
+            table
+                .action_relocations(&action, RelocationField::Dtor)
+                .count(),
+            0
+        );
+        assert_eq!(
+            table.action_symbol(&action, RelocationField::Dtor, &NoSymbols),
+            None
+        );
+    }
+
+    #[test]
+    fn lenient_short_table() {
+        let (table, diagnostics) = decode_extab_lenient(&TABLE[..6]);
+        assert!(table.pc_actions.is_empty());
+        assert_eq!(
+            diagnostics,
+            vec![Diagnostic {
+                offset: 0,
+                error: ExtabDecodeError::ArrayTooSmall {
+                    length: 6,
+                    location: ErrorLocation::new(0, ""),
+                },
+            }]
+        );
+    }
+
+    #[test]
+    fn lenient_unterminated_table() {
+        let (table, diagnostics) = decode_extab_lenient(&TABLE[..14]);
+        assert_eq!(table.pc_actions.len(), 1);
+        assert_eq!(
+            diagnostics,
+            vec![Diagnostic {
+                offset: 12,
+                error: ExtabDecodeError::MissingPcTerminator {
+                    location: ErrorLocation {
+                        offset: 12,
+                        pc_action_index: Some(1),
+                        action_index: None,
+                        field: "terminator",
+                    },
+                },
+            }]
+        );
+    }
+
+    #[test]
+    fn lenient_bad_action() {
+        //Two chains, the first one with an invalid action type
+        let mut data = TABLE[..4].to_vec();
+        data.extend_from_slice(&[0x00, 0x00, 0x00, 0x10, 0x00, 0x04, 0x00, 0x18]);
+        data.extend_from_slice(&[0x00, 0x00, 0x00, 0x20, 0x00, 0x04, 0x00, 0x20]);
+        data.extend_from_slice(&[0x00, 0x00, 0x00, 0x00]);
+        data.extend_from_slice(&[0xFF, 0x00, 0x00, 0x08, 0x80, 0x00, 0x10, 0x00]);
+        data.extend_from_slice(&TABLE[16..]);
+        let (table, diagnostics) = decode_extab_lenient(&data);
+        //The bad action is skipped, and decoding continues at the second chain
+        assert_eq!(table.exception_actions.len(), 1);
+        assert_eq!(table.exception_actions[0].action_offset, 0x20);
+        assert_eq!(table.relocations[0].offset, 0x24);
+        assert_eq!(
+            diagnostics,
+            vec![Diagnostic {
+                offset: 0x18,
+                error: ExtabDecodeError::InvalidActionValue {
+                    value: 0x7F,
+                    location: ErrorLocation {
+                        offset: 0x18,
+                        pc_action_index: None,
+                        action_index: Some(0),
+                        field: "action_type",
+                    },
+                },
+            }]
+        );
+    }
+}
@@ -0,0 +1,807 @@
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use thiserror::Error;
+
+use crate::mem_utils;
+use crate::{DecodeOptions, ExAction, ExceptionAction, ExceptionTableData, ExtabFlags, PCAction};
+use crate::{Relocation, RelocationField};
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ListingParseError {
+    #[error("Line {line}: unexpected line \"{text}\"")]
+    UnexpectedLine { line: usize, text: String },
+    #[error("Line {line}: invalid {field} value \"{value}\"")]
+    InvalidValue {
+        line: usize,
+        field: String,
+        value: String,
+    },
+    #[error("Line {line}: unknown action type \"{name}\"")]
+    UnknownActionType { line: usize, name: String },
+    #[error("Line {line}: the action has no {field} field")]
+    MissingField { line: usize, field: &'static str },
+    #[error("Line {line}: no action starts at offset 0x{offset:X}")]
+    UnknownActionOffset { line: usize, offset: u32 },
+    #[error("Line {line}: another action already starts at offset 0x{offset:X}")]
+    DuplicateActionOffset { line: usize, offset: u32 },
+    #[error(
+        "Line {line}: the action has {expected} types, but {actual} spec type addresses are listed"
+    )]
+    SpecCountMismatch {
+        line: usize,
+        expected: u16,
+        actual: usize,
+    },
+}
+
+impl ListingParseError {
+    /// Returns the line number the error happened at, starting from 1.
+    pub fn line(&self) -> usize {
+        match *self {
+            ListingParseError::UnexpectedLine { line, .. }
+            | ListingParseError::InvalidValue { line, .. }
+            | ListingParseError::UnknownActionType { line, .. }
+            | ListingParseError::MissingField { line, .. }
+            | ListingParseError::UnknownActionOffset { line, .. }
+            | ListingParseError::DuplicateActionOffset { line, .. }
+            | ListingParseError::SpecCountMismatch { line, .. } => line,
+        }
+    }
+}
+
+/// Names of the action fields read from the listing. The catch clause and type list lines
+/// are left out, since they are read from the type descriptors.
+const ACTION_FIELDS: [&str; 17] = [
+    "Param",
+    "Action",
+    "Local",
+    "Cond",
+    "Pointer",
+    "Array",
+    "Elements",
+    "Size",
+    "Member",
+    "PC",
+    "catch_type_addr",
+    "Types",
+    "Dtor",
+    "catch_type",
+    "unk0",
+    "unk4",
+    "unk8",
+];
+
+enum Section {
+    None,
+    Flags,
+    PcActions,
+    ExceptionActions,
+}
+
+/// Value of a line in the listing, along with its key and line number.
+#[derive(Debug, Copy, Clone)]
+struct Field<'a> {
+    line: usize,
+    key: &'a str,
+    value: &'a str,
+}
+
+impl<'a> Field<'a> {
+    fn invalid(&self) -> ListingParseError {
+        ListingParseError::InvalidValue {
+            line: self.line,
+            field: self.key.to_string(),
+            value: self.value.to_string(),
+        }
+    }
+
+    fn hex(&self) -> Result<u32, ListingParseError> {
+        parse_hex(self.value).ok_or_else(|| self.invalid())
+    }
+
+    fn decimal(&self) -> Result<u32, ListingParseError> {
+        self.value.parse::<u32>().map_err(|_| self.invalid())
+    }
+
+    /// Parses a location, like `0x8(SP)` or `r30`. Returns the frame offset or register
+    /// number, and whether the location is a register.
+    fn location(&self) -> Result<(u32, bool), ListingParseError> {
+        match frame_location(self.value) {
+            Some(offset) => Ok((parse_hex(offset).ok_or_else(|| self.invalid())?, false)),
+            None => Ok((
+                parse_register(self.value).ok_or_else(|| self.invalid())?,
+                true,
+            )),
+        }
+    }
+
+    /// Parses a location that can only be a frame offset.
+    fn frame_offset(&self) -> Result<u32, ListingParseError> {
+        match self.location()? {
+            (offset, false) => Ok(offset),
+            (_, true) => Err(self.invalid()),
+        }
+    }
+
+    /// Parses a member of the object at a location, like `0x8(SP)+0x4` or `0x4(r30)`.
+    /// Returns the location value, the member offset, and whether the location is a
+    /// register.
+    fn member(&self) -> Result<(u32, u32, bool), ListingParseError> {
+        let value = self.value;
+        let parsed = match value.rsplit_once('+') {
+            Some((location, member)) => frame_location(location)
+                .and_then(parse_hex)
+                .zip(parse_hex(member))
+                .map(|(offset, member)| (offset, member, false)),
+            None => value
+                .strip_suffix(')')
+                .and_then(|value| value.split_once('('))
+                .and_then(|(member, reg)| Some((parse_register(reg)?, parse_hex(member)?, true))),
+        };
+        parsed.ok_or_else(|| self.invalid())
+    }
+
+    /// Returns the name in a quoted value, like a dtor or type info symbol.
+    fn symbol(&self) -> Option<&'a str> {
+        self.value.strip_prefix('"')?.strip_suffix('"')
+    }
+}
+
+/// Exception action read from the listing, before its data is built.
+struct ListingAction<'a> {
+    line: usize,
+    /// Offset the action was listed at, which may differ from its offset in the new table
+    label: u32,
+    action_type: Option<ExAction>,
+    fields: BTreeMap<&'a str, Field<'a>>,
+    spec_types: Vec<Field<'a>>,
+    has_end_bit: bool,
+}
+
+impl<'a> ListingAction<'a> {
+    fn field(&self, key: &'static str) -> Result<Field<'a>, ListingParseError> {
+        self.fields
+            .get(key)
+            .copied()
+            .ok_or(ListingParseError::MissingField {
+                line: self.line,
+                field: key,
+            })
+    }
+
+    fn action_type(&self) -> Result<ExAction, ListingParseError> {
+        self.action_type.ok_or(ListingParseError::MissingField {
+            line: self.line,
+            field: "Type",
+        })
+    }
+
+    fn spec_count(&self) -> Result<u16, ListingParseError> {
+        match self.action_type()? {
+            ExAction::Specification => {
+                let field = self.field("Types")?;
+                u16::try_from(field.decimal()?).map_err(|_| field.invalid())
+            }
+            _ => Ok(0),
+        }
+    }
+}
+
+/// Parses the text written by `ExceptionTableData::render` back into a table. See
+/// `parse_listing_with_options`.
+pub fn parse_listing(text: &str) -> Result<ExceptionTableData, ListingParseError> {
+    parse_listing_with_options(text, DecodeOptions::default())
+}
+
+/// Parses the text written by `ExceptionTableData::render` back into a table, using the
+/// given byte order and target for the action data.
+///
+/// The text must use hex offsets. Indentation, letter case of hex values and the raw bytes
+/// after the action offsets don't matter. Action offsets are only used as labels: the
+/// actions are laid out again in order, and the PC actions and branches that point to them
+/// are updated, so actions can be added or removed by editing the text. Dtor and catch type
+/// names become the symbols of their relocations, with a zero address.
+///
+/// The unknown fields, the ET field and unknown bits of the header, and the raw action
+/// params are only listed when the text is written with `omit_unknown_fields` off. Missing
+/// values are zero, and missing params are made from the register bits of the locations.
+/// A listed param is used as is, so it has to be edited along with the locations.
+///
+/// Returns an error with the line number if a line can't be parsed.
+pub fn parse_listing_with_options(
+    text: &str,
+    options: DecodeOptions,
+) -> Result<ExceptionTableData, ListingParseError> {
+    let mut extab_data = ExceptionTableData::new();
+    extab_data.options = options;
+    let mut section = Section::None;
+    let mut pc_actions: Vec<(usize, PCAction)> = vec![];
+    let mut actions: Vec<ListingAction> = vec![];
+
+    for (index, line_text) in text.lines().enumerate() {
+        let line = index + 1;
+        let text = line_text.trim();
+        let unexpected = || ListingParseError::UnexpectedLine {
+            line,
+            text: text.to_string(),
+        };
+        match text {
+            "" => continue,
+            "Flag values:" => section = Section::Flags,
+            "PC actions:" => section = Section::PcActions,
+            "Exception actions:" => section = Section::ExceptionActions,
+            _ => match section {
+                Section::None => return Err(unexpected()),
+                Section::Flags => parse_flag(&mut extab_data.flags, line, text)?,
+                Section::PcActions => pc_actions.push((line, parse_pc_action(line, text)?)),
+                Section::ExceptionActions => {
+                    //Each action starts with its offset, followed by its fields
+                    if let Some(label) = parse_action_label(text) {
+                        actions.push(ListingAction {
+                            line,
+                            label,
+                            action_type: None,
+                            fields: BTreeMap::new(),
+                            spec_types: vec![],
+                            has_end_bit: false,
+                        });
+                        continue;
+                    }
+                    let action = actions.last_mut().ok_or_else(unexpected)?;
+                    if text == "Has end bit" {
+                        action.has_end_bit = true;
+                        continue;
+                    }
+                    let (key, value) = text.split_once(": ").ok_or_else(unexpected)?;
+                    let field = Field { line, key, value };
+                    match key {
+                        "Type" => {
+                            let action_type = parse_action_type(value).ok_or_else(|| {
+                                ListingParseError::UnknownActionType {
+                                    line,
+                                    name: value.to_string(),
+                                }
+                            })?;
+                            action.action_type = Some(action_type);
+                        }
+                        "spec_type_addr" => action.spec_types.push(field),
+                        "Catch" | "Throws" => {}
+                        _ if ACTION_FIELDS.contains(&key) => {
+                            if action.fields.insert(key, field).is_some() {
+                                return Err(unexpected());
+                            }
+                        }
+                        _ => return Err(unexpected()),
+                    }
+                }
+            },
+        }
+    }
+
+    //Lay out the actions again, and map the listed offsets to the new ones
+    let mut offset = 4 + 8 * pc_actions.len() as u32 + 4;
+    let mut offsets: BTreeMap<u32, u32> = BTreeMap::new();
+    for action in &actions {
+        let action_type = action.action_type()?;
+        if offsets.insert(action.label, offset).is_some() {
+            return Err(ListingParseError::DuplicateActionOffset {
+                line: action.line,
+                offset: action.label,
+            });
+        }
+        offset += 2 + action_type.data_size(action.spec_count()?) as u32;
+    }
+    let action_offset = |line: usize, label: u32| -> Result<u32, ListingParseError> {
+        match offsets.get(&label) {
+            Some(&offset) => Ok(offset),
+            None if actions.is_empty() => Ok(label),
+            None => Err(ListingParseError::UnknownActionOffset {
+                line,
+                offset: label,
+            }),
+        }
+    };
+
+    for (line, mut pcaction) in pc_actions {
+        pcaction.action_offset = action_offset(line, pcaction.action_offset)?;
+        extab_data.pc_actions.push(pcaction);
+    }
+    for action in &actions {
+        let offset = action_offset(action.line, action.label)?;
+        let (exaction, relocations) = build_action(action, offset, options, &action_offset)?;
+        extab_data.exception_actions.push(exaction);
+        extab_data.relocations.extend(relocations);
+    }
+    Ok(extab_data)
+}
+
+/// Builds the data of an action read from the listing, along with its relocations.
+fn build_action<'a, F>(
+    action: &ListingAction<'a>,
+    action_offset: u32,
+    options: DecodeOptions,
+    offsets: &F,
+) -> Result<(ExceptionAction, Vec<Relocation>), ListingParseError>
+where
+    F: Fn(usize, u32) -> Result<u32, ListingParseError>,
+{
+    let action_type = action.action_type()?;
+    let spec_count = action.spec_count()?;
+    let mut values: BTreeMap<&'static str, (u32, Option<Field<'a>>)> = BTreeMap::new();
+    let mut param: u8 = 0;
+
+    //Read the fields shown for the action type. Registers are selected with bit 7 of the
+    //param, or bit 6 for the object of conditional actions.
+    let mut set = |name: &'static str, value: u32, field: Field<'a>| {
+        values.insert(name, (value, Some(field)));
+    };
+    match action_type {
+        ExAction::EndOfList | ExAction::Terminate => {}
+        ExAction::Branch => {
+            let field = action.field("Action")?;
+            set("target_offset", offsets(field.line, field.hex()?)?, field);
+        }
+        ExAction::DestroyLocal => {
+            let field = action.field("Local")?;
+            set("local_offset", field.frame_offset()?, field);
+        }
+        ExAction::DestroyLocalCond => {
+            let field = action.field("Cond")?;
+            let (condition, is_register) = field.location()?;
+            set("condition", condition, field);
+            param = is_register as u8;
+            let field = action.field("Local")?;
+            set("local_offset", field.frame_offset()?, field);
+        }
+        ExAction::DestroyLocalPointer | ExAction::DeletePointer => {
+            let field = action.field("Pointer")?;
+            let (pointer, is_register) = field.location()?;
+            let name = match action_type {
+                ExAction::DestroyLocalPointer => "local_pointer",
+                _ => "object_pointer",
+            };
+            set(name, pointer, field);
+            param = (is_register as u8) << 7;
+        }
+        ExAction::DestroyLocalArray => {
+            let field = action.field("Array")?;
+            set("local_array", field.frame_offset()?, field);
+            let field = action.field("Elements")?;
+            set("elements", field.decimal()?, field);
+            let field = action.field("Size")?;
+            set("element_size", field.decimal()?, field);
+        }
+        ExAction::DestroyBase | ExAction::DestroyMember | ExAction::DestroyMemberArray => {
+            let field = action.field("Member")?;
+            let (object, member_offset, is_register) = field.member()?;
+            set("object_pointer", object, field);
+            set("member_offset", member_offset, field);
+            param = (is_register as u8) << 7;
+            if matches!(action_type, ExAction::DestroyMemberArray) {
+                let field = action.field("Elements")?;
+                set("elements", field.decimal()?, field);
+                let field = action.field("Size")?;
+                set("element_size", field.decimal()?, field);
+            }
+        }
+        ExAction::DestroyMemberCond | ExAction::DeletePointerCond => {
+            let field = action.field("Cond")?;
+            let (condition, cond_register) = field.location()?;
+            set("condition", condition, field);
+            let object_register = match action_type {
+                ExAction::DestroyMemberCond => {
+                    let field = action.field("Member")?;
+                    let (object, member_offset, is_register) = field.member()?;
+                    set("object_pointer", object, field);
+                    set("member_offset", member_offset, field);
+                    is_register
+                }
+                _ => {
+                    let field = action.field("Pointer")?;
+                    let (pointer, is_register) = field.location()?;
+                    set("object_pointer", pointer, field);
+                    is_register
+                }
+            };
+            param = ((cond_register as u8) << 7) | ((object_register as u8) << 6);
+        }
+        ExAction::CatchBlock | ExAction::CatchBlock32 => {
+            let field = action.field("Local")?;
+            set("cinfo_ref", field.frame_offset()?, field);
+            let field = action.field("PC")?;
+            set("catch_pc_offset", field.hex()?, field);
+            let field = action.field("catch_type_addr")?;
+            set("catch_type", field.hex()?, field);
+        }
+        ExAction::ActiveCatchBlock => {
+            let field = action.field("Local")?;
+            set("cinfo_ref", field.frame_offset()?, field);
+        }
+        ExAction::Specification => {
+            let field = action.field("Local")?;
+            set("cinfo_ref", field.frame_offset()?, field);
+            let field = action.field("PC")?;
+            set("pc_offset", field.hex()?, field);
+            set("specs", spec_count as u32, action.field("Types")?);
+        }
+    }
+    if let Some(field) = action.fields.get("Param") {
+        param = u8::try_from(field.hex()?).map_err(|_| field.invalid())?;
+    }
+    for name in ["unk0", "unk4", "unk8"] {
+        if let Some(&field) = action.fields.get(name) {
+            set(name, field.hex()?, field);
+        }
+    }
+
+    //Dtors and catch types are listed by name when their symbol is known
+    let mut dtor_symbol = None;
+    if action_type.has_dtor_ref() {
+        if let Some(&field) = action.fields.get("Dtor") {
+            match field.symbol() {
+                Some(name) => dtor_symbol = Some(name),
+                None => set("dtor_address", field.hex()?, field),
+            }
+        }
+    }
+    let catch_symbol = action
+        .fields
+        .get("catch_type")
+        .and_then(|field| field.symbol());
+
+    let mut spec_types: Vec<(u32, Option<Field<'a>>)> = vec![];
+    for field in &action.spec_types {
+        spec_types.push((field.hex()?, Some(*field)));
+    }
+    if !spec_types.is_empty() && spec_types.len() != spec_count as usize {
+        return Err(ListingParseError::SpecCountMismatch {
+            line: action.field("Types")?.line,
+            expected: spec_count,
+            actual: spec_types.len(),
+        });
+    }
+
+    //Write the values in the order of the action data
+    let mut bytes = vec![];
+    let mut spec_values = spec_types.into_iter();
+    for (name, size) in action_type.field_layout(spec_count) {
+        let (value, field) = match name {
+            "spec" => spec_values.next(),
+            _ => values.get(name).copied(),
+        }
+        .unwrap_or((0, None));
+        if size == 2 {
+            let value = u16::try_from(value).map_err(|_| match field {
+                Some(field) => field.invalid(),
+                None => ListingParseError::MissingField {
+                    line: action.line,
+                    field: name,
+                },
+            })?;
+            mem_utils::write_uint16(&mut bytes, value, options.endian);
+        } else {
+            mem_utils::write_uint32(&mut bytes, value, options.endian);
+        }
+    }
+
+    let exaction = ExceptionAction {
+        action_offset,
+        action_type,
+        action_param: param,
+        has_end_bit: action.has_end_bit,
+        bytes,
+    };
+    let mut relocations = exaction
+        .get_relocations_with_options(options)
+        .unwrap_or_default();
+    for relocation in &mut relocations {
+        let symbol = match relocation.field {
+            RelocationField::Dtor => dtor_symbol,
+            RelocationField::CatchType => catch_symbol,
+            RelocationField::SpecType => None,
+        };
+        relocation.symbol = symbol.map(String::from);
+    }
+    Ok((exaction, relocations))
+}
+
+fn parse_flag(flags: &mut ExtabFlags, line: usize, text: &str) -> Result<(), ListingParseError> {
+    let (key, value) = text
+        .split_once(": ")
+        .ok_or_else(|| ListingParseError::UnexpectedLine {
+            line,
+            text: text.to_string(),
+        })?;
+    let field = Field { line, key, value };
+    let yes_no = || match value {
+        "Yes" => Ok(true),
+        "No" => Ok(false),
+        _ => Err(field.invalid()),
+    };
+    //Saved registers are listed as a range up to register 31
+    let save_count = || {
+        let first = value.split('-').next().unwrap_or_default();
+        let start = parse_register(first).filter(|&start| start <= 31);
+        start
+            .map(|start| (32 - start) as u8)
+            .ok_or_else(|| field.invalid())
+    };
+    match key {
+        "Has Elf Vector" => flags.set_has_elf_vector(yes_no()?),
+        "Large Frame" => flags.set_large_frame(yes_no()?),
+        "Has Frame Pointer" => flags.set_has_frame_pointer(yes_no()?),
+        "Saved CR" => flags.set_saved_cr(yes_no()?),
+        "Saved FPR range" => flags
+            .set_fpr_save_count(save_count()?)
+            .map_err(|_| field.invalid())?,
+        "Saved GPR range" => flags
+            .set_gpr_save_count(save_count()?)
+            .map_err(|_| field.invalid())?,
+        "ET field" => {
+            let et_field = field.hex()?;
+            flags.set_et_field(u16::try_from(et_field).map_err(|_| field.invalid())?);
+        }
+        "Unknown bits" => {
+            let bits = field.hex()?;
+            if bits & !(ExtabFlags::UNKNOWN_BITS as u32) != 0 {
+                return Err(field.invalid());
+            }
+            flags.set_unknown_bits(bits as u16);
+        }
+        _ => {
+            return Err(ListingParseError::UnexpectedLine {
+                line,
+                text: text.to_string(),
+            })
+        }
+    }
+    Ok(())
+}
+
+/// Parses a PC action line, like `PC=00000010:00000040, Action: 000010`. The action
+/// offset is the listed one, and has to be mapped to the new layout.
+fn parse_pc_action(line: usize, text: &str) -> Result<PCAction, ListingParseError> {
+    let (range, action) = text
+        .strip_prefix("PC=")
+        .and_then(|rest| rest.split_once(", Action: "))
+        .ok_or_else(|| ListingParseError::UnexpectedLine {
+            line,
+            text: text.to_string(),
+        })?;
+    let (start_pc, end_pc) = range.split_once(':').unwrap_or((range, range));
+    let field = |key, value| Field { line, key, value };
+    Ok(PCAction {
+        start_pc: field("PC", start_pc).hex()?,
+        end_pc: field("PC", end_pc).hex()?,
+        action_offset: field("Action", action).hex()?,
+    })
+}
+
+/// Returns the offset of an action header line, like `000010:`, which may be followed by
+/// the raw bytes of the action.
+fn parse_action_label(text: &str) -> Option<u32> {
+    let (label, _) = text.split_once(':')?;
+    if label.is_empty() || !label.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    u32::from_str_radix(label, 16).ok()
+}
+
+fn parse_action_type(name: &str) -> Option<ExAction> {
+    let index = ExAction::ACTION_NAMES
+        .iter()
+        .position(|&action_name| action_name == name)?;
+    ExAction::from_int(index as i32)
+}
+
+fn parse_hex(text: &str) -> Option<u32> {
+    let digits = text
+        .strip_prefix("0x")
+        .or_else(|| text.strip_prefix("0X"))
+        .unwrap_or(text);
+    u32::from_str_radix(digits, 16).ok()
+}
+
+/// Parses a register name of any target, like `r30`, `fp14` or `$f20`, into its number.
+fn parse_register(name: &str) -> Option<u32> {
+    let number = name.trim_start_matches(|c: char| !c.is_ascii_digit());
+    if number.is_empty() || number.len() == name.len() {
+        return None;
+    }
+    number.parse::<u32>().ok()
+}
+
+/// Returns the offset part of a frame location, like `0x8` in `0x8(SP)`.
+fn frame_location(text: &str) -> Option<&str> {
+    let (offset, base) = text.strip_suffix(')')?.split_once('(')?;
+    let base = base.to_ascii_lowercase();
+    matches!(base.as_str(), "sp" | "fp" | "$sp" | "$fp").then(|| offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        decode_extab_with_options, encode_extab, Endian, ExtabBuilder, FormatOptions, FrameBase,
+        Location, NoSymbols, Target,
+    };
+    use alloc::format;
+
+    fn sample_table(options: DecodeOptions) -> ExceptionTableData {
+        let sp = |offset| Location::FrameOffset {
+            base: FrameBase::Sp,
+            offset,
+        };
+        let mut builder = ExtabBuilder::with_options(options);
+        let first = builder.new_label();
+        let second = builder.new_label();
+        let tail = builder.new_label();
+        builder
+            .begin_chain(first)
+            .destroy_local(0x8, 0x80001000)
+            .destroy_local_cond(sp(0xC), 0x10, 0x80001004)
+            .delete_pointer_cond(
+                Location::Register(0xCE1D),
+                Location::Register(30),
+                0x80001008,
+            )
+            .branch_to(tail)
+            .begin_chain(second)
+            .destroy_member(Location::Register(31), 0x4, 0x8000100C)
+            .destroy_local_array(0x20, 3, 8, 0x80001010)
+            .bind(tail)
+            .catch_block(0x80002000, 0x40, 0x28)
+            .specification(0x50, 0x30, &[0x80002004, 0x80002008])
+            .terminate();
+        builder.pc_range(0x10, 0x20, first);
+        builder.pc_range(0x40, 0x8, second);
+        builder.build().unwrap()
+    }
+
+    /// Renders the table with every value shown, and parses it back.
+    fn render_and_parse(table: &ExceptionTableData) -> ExceptionTableData {
+        let format = FormatOptions {
+            omit_unknown_fields: false,
+            ..FormatOptions::default()
+        };
+        let text = table.render_with_options(NoSymbols, format).to_string();
+        parse_listing_with_options(&text, table.options).unwrap()
+    }
+
+    #[test]
+    fn round_trip() {
+        for endian in [Endian::Big, Endian::Little] {
+            let options = DecodeOptions {
+                endian,
+                target: Target::PowerPc,
+            };
+            let bytes = encode_extab(&sample_table(options)).unwrap();
+            let table = decode_extab_with_options(&bytes, options).unwrap();
+            assert_eq!(encode_extab(&render_and_parse(&table)).unwrap(), bytes);
+        }
+    }
+
+    #[test]
+    fn round_trip_unknown_values() {
+        let mut table = sample_table(DecodeOptions::default());
+        table.flags = ExtabFlags::from_raw(0x18A9, 0x1234);
+        //Params with bits that don't select registers
+        table.exception_actions[1].action_param = 0x9E;
+        let last = table.exception_actions.len() - 1;
+        table.exception_actions[last].action_param = 0x73;
+        let bytes = encode_extab(&table).unwrap();
+
+        let parsed = render_and_parse(&table);
+        assert_eq!(parsed.flags, table.flags);
+        assert_eq!(parsed.exception_actions[1].action_param, 0x9E);
+        assert_eq!(parsed.exception_actions[last].action_param, 0x73);
+        assert_eq!(encode_extab(&parsed).unwrap(), bytes);
+    }
+
+    #[test]
+    fn default_format_drops_unknown_values() {
+        let mut table = sample_table(DecodeOptions::default());
+        table.flags = ExtabFlags::from_raw(0x18A9, 0x1234);
+        let parsed = parse_listing(&table.to_string()).unwrap();
+        assert_eq!(parsed.flags, ExtabFlags::from_raw(0x18A8, 0));
+    }
+
+    #[test]
+    fn edited_listing_moves_labels() {
+        let text = "\
+Flag values:
+Has Elf Vector: No
+Large Frame: No
+Has Frame Pointer: No
+Saved CR: No
+
+PC actions:
+PC=00000010:00000020, Action: 000100
+
+Exception actions:
+000100:
+Type: DESTROYLOCAL
+Local: 0x8(SP)
+Dtor: \"__dt__3FooFv\"
+000200:
+Type: TERMINATE
+Has end bit
+";
+        let table = parse_listing(text).unwrap();
+        assert_eq!(table.pc_actions[0].action_offset, 0x10);
+        assert_eq!(table.exception_actions[1].action_offset, 0x18);
+        assert_eq!(table.relocations.len(), 1);
+        assert_eq!(table.relocations[0].offset, 0x14);
+        assert_eq!(table.relocations[0].address, 0);
+        assert_eq!(table.relocations[0].symbol.as_deref(), Some("__dt__3FooFv"));
+    }
+
+    #[test]
+    fn errors_have_line_numbers() {
+        let text = sample_table(DecodeOptions::default()).to_string();
+        let replace_line = |line: usize, new_text: &str| {
+            let mut lines: Vec<&str> = text.lines().collect();
+            lines[line - 1] = new_text;
+            lines.join("\n")
+        };
+        let line_of = |prefix: &str| text.lines().position(|l| l.starts_with(prefix)).unwrap() + 1;
+
+        let local = line_of("Local: 0x8(SP)");
+        let error = parse_listing(&replace_line(local, "Local: r8")).unwrap_err();
+        assert_eq!(error.line(), local);
+        assert!(matches!(error, ListingParseError::InvalidValue { .. }));
+
+        let action_type = line_of("Type: DESTROYLOCAL");
+        let error = parse_listing(&replace_line(action_type, "Type: DESTROYLOCL")).unwrap_err();
+        assert_eq!(
+            error,
+            ListingParseError::UnknownActionType {
+                line: action_type,
+                name: "DESTROYLOCL".to_string(),
+            }
+        );
+
+        let pc = line_of("PC=00000010");
+        let moved = replace_line(pc, "PC=00000010:00000030, Action: 000011");
+        assert_eq!(
+            parse_listing(&moved).unwrap_err(),
+            ListingParseError::UnknownActionOffset {
+                line: pc,
+                offset: 0x11,
+            }
+        );
+
+        //Two actions can't share a label
+        let table = sample_table(DecodeOptions::default());
+        let first = table.exception_actions[0].action_offset;
+        let second = line_of(&format!(
+            "{:06X}:",
+            table.exception_actions[1].action_offset
+        ));
+        let duplicate = replace_line(second, &format!("{first:06X}:"));
+        assert_eq!(
+            parse_listing(&duplicate).unwrap_err(),
+            ListingParseError::DuplicateActionOffset {
+                line: second,
+                offset: first,
+            }
+        );
+
+        let end_bit = line_of("Has end bit");
+        let error = parse_listing(&replace_line(end_bit, "Has end bits")).unwrap_err();
+        assert_eq!(error.line(), end_bit);
+        assert!(matches!(error, ListingParseError::UnexpectedLine { .. }));
+
+        //Missing fields are reported at the action offset line
+        let error = parse_listing(&replace_line(local, "")).unwrap_err();
+        assert_eq!(
+            error,
+            ListingParseError::MissingField {
+                line: local - 2,
+                field: "Local",
+            }
+        );
+    }
+}
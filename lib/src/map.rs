@@ -0,0 +1,335 @@
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use thiserror::Error;
+
+use crate::ExceptionTableData;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum MapError {
+    #[error("Invalid symbol entry on line {line}")]
+    InvalidEntry { line: usize },
+    #[error("Map file has no section layout")]
+    NoSections,
+}
+
+/// Struct for a symbol from the section layout of a map file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MapSymbol {
+    pub name: String,
+    /// Name of the section the symbol is in
+    pub section: String,
+    /// Offset of the symbol in the section, before linking
+    pub start: u32,
+    pub size: u32,
+    /// Virtual address of the symbol
+    pub address: u32,
+    /// File offset of the symbol, if the map has the column
+    pub file_offset: Option<u32>,
+    /// Alignment of the symbol, if the map has the column
+    pub alignment: Option<u32>,
+    /// Object file (and library) the symbol comes from
+    pub object: String,
+}
+
+impl MapSymbol {
+    /// Returns whether the entry is the start of a section in an object file rather than a
+    /// symbol, like `.text main.o`.
+    pub fn is_section_entry(&self) -> bool {
+        self.name.starts_with('.')
+    }
+}
+
+/// Parsed CodeWarrior linker map file.
+#[derive(Debug, Clone, Default)]
+pub struct MapFile {
+    symbols: Vec<MapSymbol>,
+    by_address: Vec<usize>, //Indices of the symbols, sorted by address
+}
+
+impl MapFile {
+    /// Parses the section layouts of the provided map file.
+    ///
+    /// Each `<section> section layout` table is read, with or without the file offset and
+    /// alignment columns. Unused symbols, which have no address, are left out.
+    pub fn parse(text: &str) -> Result<Self, MapError> {
+        let mut symbols = vec![];
+        let mut section: Option<String> = None;
+        let mut found_section = false;
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim_end();
+            if let Some(name) = line.strip_suffix(" section layout") {
+                section = Some(String::from(name.trim()));
+                found_section = true;
+                continue;
+            }
+            let section_name = match &section {
+                Some(name) => name,
+                None => continue,
+            };
+            let trimmed = line.trim_start();
+            if trimmed.is_empty() {
+                continue;
+            }
+            //The layouts end at the memory map and the linker generated symbols
+            if !line.starts_with(char::is_whitespace) {
+                section = None;
+                continue;
+            }
+            let first = trimmed.split_whitespace().next().unwrap_or_default();
+            if first == "UNUSED" || !is_hex(first) {
+                //Column headers and unused symbols
+                continue;
+            }
+            let symbol =
+                parse_entry(trimmed, section_name).ok_or(MapError::InvalidEntry { line: i + 1 })?;
+            symbols.push(symbol);
+        }
+
+        if !found_section {
+            return Err(MapError::NoSections);
+        }
+        let mut by_address: Vec<usize> = (0..symbols.len()).collect();
+        by_address.sort_by_key(|&index| symbols[index].address);
+        Ok(Self {
+            symbols,
+            by_address,
+        })
+    }
+
+    /// Returns the symbols of the map, in the order of the section layouts.
+    pub fn symbols(&self) -> &[MapSymbol] {
+        &self.symbols
+    }
+
+    /// Returns the symbol at the given address, if any. Symbols are preferred over section
+    /// entries at the same address.
+    pub fn symbol_at(&self, address: u32) -> Option<&MapSymbol> {
+        let start = self
+            .by_address
+            .partition_point(|&index| self.symbols[index].address < address);
+        let mut result: Option<&MapSymbol> = None;
+        for &index in &self.by_address[start..] {
+            let symbol = &self.symbols[index];
+            if symbol.address != address {
+                break;
+            }
+            if result.is_none() || !symbol.is_section_entry() {
+                result = Some(symbol);
+            }
+            if !symbol.is_section_entry() {
+                break;
+            }
+        }
+        result
+    }
+
+    /// Returns the symbol that contains the given address, if any.
+    pub fn symbol_containing(&self, address: u32) -> Option<&MapSymbol> {
+        let end = self
+            .by_address
+            .partition_point(|&index| self.symbols[index].address <= address);
+        self.by_address[..end]
+            .iter()
+            .rev()
+            .map(|&index| &self.symbols[index])
+            .filter(|symbol| !symbol.is_section_entry())
+            .find(|symbol| address - symbol.address < symbol.size.max(1))
+    }
+
+    /// Returns the name of the function that starts at the given address, if any.
+    pub fn function_name(&self, address: u32) -> Option<&str> {
+        self.symbol_at(address)
+            .filter(|symbol| !symbol.is_section_entry())
+            .map(|symbol| symbol.name.as_str())
+    }
+
+    /// Sets the symbol of every relocation in the table from its address, for the dtor and
+    /// type info addresses of a linked table. Relocations that already have a symbol are
+    /// kept as is.
+    pub fn resolve_relocations(&self, table: &mut ExceptionTableData) {
+        table.resolve_symbols(self);
+    }
+}
+
+fn is_hex(value: &str) -> bool {
+    !value.is_empty() && value.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn parse_hex(value: &str) -> Option<u32> {
+    u32::from_str_radix(value, 16).ok()
+}
+
+/// Parses a symbol entry, like `00000000 000080 80005640 00002740  4 main main.o`. The file
+/// offset column is only present in newer maps, and the alignment column is missing from
+/// the oldest ones, like `00000000 000080 80005640 main main.o`.
+fn parse_entry(line: &str, section: &str) -> Option<MapSymbol> {
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    let start = parse_hex(tokens.first()?)?;
+    let size = parse_hex(tokens.get(1)?)?;
+    let address = parse_hex(tokens.get(2)?)?;
+
+    let is_alignment = |token: &str| token.chars().all(|c| c.is_ascii_digit());
+    let (file_offset, rest) = match (tokens.get(3), tokens.get(4)) {
+        (Some(&offset), Some(&alignment))
+            if offset.len() == 8 && is_hex(offset) && is_alignment(alignment) =>
+        {
+            (parse_hex(offset), &tokens[4..])
+        }
+        _ => (None, &tokens[3..]),
+    };
+    let (alignment, rest) = match rest.split_first() {
+        Some((&alignment, names)) if is_alignment(alignment) && !names.is_empty() => {
+            (alignment.parse::<u32>().ok(), names)
+        }
+        _ => (None, rest),
+    };
+    let name = *rest.first()?;
+    let object = rest[1..].join(" ");
+
+    Some(MapSymbol {
+        name: String::from(name),
+        section: String::from(section),
+        start,
+        size,
+        address,
+        file_offset,
+        alignment,
+        object,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode_extab;
+
+    /// Layout of the oldest maps, without the file offset and alignment columns.
+    const OLD_MAP: &str = "Link map of __start
+
+.init section layout
+  Starting        Virtual
+  address  Size   address
+  -----------------------
+  00000000 000110 80003100 .init 	os.a __start.o
+  00000000 000078 80003100 __start 	os.a __start.o
+  00000078 000098 80003178 __init_registers 	os.a __start.o
+
+.text section layout
+  Starting        Virtual
+  address  Size   address
+  -----------------------
+  00000000 000040 80003210 main 	main.o
+  UNUSED   000030 ........ unused 	main.o
+";
+
+    /// Layout of newer maps, with the file offset and alignment columns.
+    const NEW_MAP: &str = "Link map of __start
+
+.text section layout
+  Starting        Virtual  File
+  address  Size   address  offset
+  ---------------------------------
+  00000000 000080 80005640 00002740  4 .text 	main.o
+  00000000 000040 80005640 00002740  4 main 	main.o
+  00000040 000040 80001000 000000C0  4 __dt__7CObjectFv 	object.o
+  UNUSED   000030 ........ ........    unused main.o
+
+.data section layout
+  Starting        Virtual  File
+  address  Size   address  offset
+  ---------------------------------
+  00000000 000008 80006000 00003100 32 lbl_80006000 	main.o
+
+
+Memory map:
+                   Starting Size     File
+                   address           Offset
+           .init  80003100 00002440 00000100
+";
+
+    #[test]
+    fn parses_old_layout() {
+        let map = MapFile::parse(OLD_MAP).unwrap();
+        let symbols = map.symbols();
+        assert_eq!(symbols.len(), 4);
+        assert_eq!(
+            symbols[2],
+            MapSymbol {
+                name: String::from("__init_registers"),
+                section: String::from(".init"),
+                start: 0x78,
+                size: 0x98,
+                address: 0x80003178,
+                file_offset: None,
+                alignment: None,
+                object: String::from("os.a __start.o"),
+            }
+        );
+        assert!(symbols[0].is_section_entry());
+        assert_eq!(symbols[3].section, ".text");
+        assert_eq!(map.function_name(0x80003100), Some("__start"));
+        assert_eq!(map.function_name(0x80003210), Some("main"));
+    }
+
+    #[test]
+    fn parses_new_layout() {
+        let map = MapFile::parse(NEW_MAP).unwrap();
+        let symbols = map.symbols();
+        //The memory map is not part of the layouts
+        assert_eq!(symbols.len(), 4);
+        assert_eq!(
+            symbols[1],
+            MapSymbol {
+                name: String::from("main"),
+                section: String::from(".text"),
+                start: 0,
+                size: 0x40,
+                address: 0x80005640,
+                file_offset: Some(0x2740),
+                alignment: Some(4),
+                object: String::from("main.o"),
+            }
+        );
+        assert_eq!(symbols[3].alignment, Some(32));
+        assert_eq!(symbols[3].section, ".data");
+    }
+
+    #[test]
+    fn finds_symbols() {
+        let map = MapFile::parse(NEW_MAP).unwrap();
+        //Symbols are preferred over section entries at the same address
+        assert_eq!(map.symbol_at(0x80005640).unwrap().name, "main");
+        assert_eq!(map.symbol_at(0x80005644), None);
+        assert_eq!(map.symbol_containing(0x80005644).unwrap().name, "main");
+        assert_eq!(map.symbol_containing(0x80005680), None);
+        assert_eq!(map.function_name(0x80006000), Some("lbl_80006000"));
+
+        let mut table = decode_extab(&[
+            0x00, 0x00, 0x00, 0x00, //Header
+            0x00, 0x00, 0x00, 0x04, 0x00, 0x02, 0x00, 0x10, //PC action
+            0x00, 0x00, 0x00, 0x00, //Terminator
+            0x82, 0x00, 0x00, 0x08, 0x80, 0x00, 0x10, 0x00, //DestroyLocal
+        ])
+        .unwrap();
+        map.resolve_relocations(&mut table);
+        assert_eq!(
+            table.relocations[0].symbol.as_deref(),
+            Some("__dt__7CObjectFv")
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_maps() {
+        assert_eq!(
+            MapFile::parse("Memory map:\n").unwrap_err(),
+            MapError::NoSections
+        );
+        let text = ".text section layout\n  00000000 000040 80005640\n";
+        assert_eq!(
+            MapFile::parse(text).unwrap_err(),
+            MapError::InvalidEntry { line: 2 }
+        );
+    }
+}
@@ -1,26 +1,92 @@
-pub fn read_byte(data: &[u8], offset: &mut i32, update_offset: bool) -> u8 {
-    let index = *offset as usize;
-    let b = data[index];
-    if update_offset {
-        *offset += 1;
+use crate::{Endian, ErrorLocation, ExtabDecodeError};
+use alloc::vec::Vec;
+
+/// Returns the slice of `size` bytes at the given offset, or an `UnexpectedEof` error
+/// if the data is too short.
+fn get_bytes<'a>(
+    data: &'a [u8],
+    offset: usize,
+    size: usize,
+    field: &'static str,
+) -> Result<&'a [u8], ExtabDecodeError> {
+    match offset.checked_add(size) {
+        Some(end) if end <= data.len() => Ok(&data[offset..end]),
+        _ => Err(ExtabDecodeError::UnexpectedEof {
+            needed: size as u32,
+            available: data.len().saturating_sub(offset) as u32,
+            location: ErrorLocation::new(offset as u32, field),
+        }),
     }
-    b
 }
 
-pub fn read_uint16(data: &[u8], offset: &mut i32, update_offset: bool) -> u16 {
-    let index = *offset as usize;
-    let bytes = data[index..index + 2].try_into().unwrap();
+pub fn read_bytes<'a>(
+    data: &'a [u8],
+    offset: &mut usize,
+    size: usize,
+    update_offset: bool,
+    field: &'static str,
+) -> Result<&'a [u8], ExtabDecodeError> {
+    let bytes = get_bytes(data, *offset, size, field)?;
     if update_offset {
-        *offset += 2;
+        *offset += size;
     }
-    u16::from_be_bytes(bytes)
+    Ok(bytes)
 }
 
-pub fn read_uint32(data: &[u8], offset: &mut i32, update_offset: bool) -> u32 {
-    let index = *offset as usize;
-    let bytes = data[index..index + 4].try_into().unwrap();
-    if update_offset {
-        *offset += 4;
+pub fn read_byte(
+    data: &[u8],
+    offset: &mut usize,
+    update_offset: bool,
+    field: &'static str,
+) -> Result<u8, ExtabDecodeError> {
+    let bytes = read_bytes(data, offset, 1, update_offset, field)?;
+    Ok(bytes[0])
+}
+
+pub fn read_uint16(
+    data: &[u8],
+    offset: &mut usize,
+    update_offset: bool,
+    field: &'static str,
+    endian: Endian,
+) -> Result<u16, ExtabDecodeError> {
+    let bytes = read_bytes(data, offset, 2, update_offset, field)?;
+    let bytes = [bytes[0], bytes[1]];
+    Ok(match endian {
+        Endian::Big => u16::from_be_bytes(bytes),
+        Endian::Little => u16::from_le_bytes(bytes),
+    })
+}
+
+pub fn read_uint32(
+    data: &[u8],
+    offset: &mut usize,
+    update_offset: bool,
+    field: &'static str,
+    endian: Endian,
+) -> Result<u32, ExtabDecodeError> {
+    let bytes = read_bytes(data, offset, 4, update_offset, field)?;
+    let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+    Ok(match endian {
+        Endian::Big => u32::from_be_bytes(bytes),
+        Endian::Little => u32::from_le_bytes(bytes),
+    })
+}
+
+pub fn write_byte(data: &mut Vec<u8>, value: u8) {
+    data.push(value);
+}
+
+pub fn write_uint16(data: &mut Vec<u8>, value: u16, endian: Endian) {
+    match endian {
+        Endian::Big => data.extend_from_slice(&value.to_be_bytes()),
+        Endian::Little => data.extend_from_slice(&value.to_le_bytes()),
+    }
+}
+
+pub fn write_uint32(data: &mut Vec<u8>, value: u32, endian: Endian) {
+    match endian {
+        Endian::Big => data.extend_from_slice(&value.to_be_bytes()),
+        Endian::Little => data.extend_from_slice(&value.to_le_bytes()),
     }
-    u32::from_be_bytes(bytes)
 }
@@ -0,0 +1,148 @@
+use alloc::format;
+use alloc::string::String;
+use core::fmt;
+
+use crate::{FrameBase, Location};
+
+/// Byte order of the exception table data.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Endian {
+    Big,
+    Little,
+}
+
+impl Default for Endian {
+    fn default() -> Self {
+        Endian::Big
+    }
+}
+
+/// Target architecture the exception tables were built for. This controls how registers
+/// are named in the text output.
+///
+/// Only PowerPC is supported, since it is the only target this table layout is known for.
+/// Tables stored in little-endian by other tools can still be read with `Endian::Little`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Target {
+    /// PowerPC (GameCube/Wii)
+    PowerPc,
+}
+
+impl Default for Target {
+    fn default() -> Self {
+        Target::PowerPc
+    }
+}
+
+impl Target {
+    /// Returns the byte order normally used by the target.
+    pub fn default_endian(&self) -> Endian {
+        match self {
+            Target::PowerPc => Endian::Big,
+        }
+    }
+
+    /// Returns the name of a general purpose register.
+    pub fn gpr_name(&self, reg: u16) -> RegisterName {
+        let prefix = match self {
+            Target::PowerPc => "r",
+        };
+        RegisterName {
+            prefix,
+            number: reg,
+        }
+    }
+
+    /// Returns the name of a floating point register.
+    pub fn fpr_name(&self, reg: u8) -> RegisterName {
+        let prefix = match self {
+            Target::PowerPc => "fp",
+        };
+        RegisterName {
+            prefix,
+            number: reg as u16,
+        }
+    }
+
+    /// Returns the name of the register frame offsets are relative to.
+    pub fn frame_base_name(&self, base: FrameBase) -> &'static str {
+        match (self, base) {
+            (Target::PowerPc, FrameBase::Sp) => "SP",
+            (Target::PowerPc, FrameBase::Fp) => "FP",
+        }
+    }
+
+    /// Formats an operand location using the register names of the target.
+    pub fn format_location(&self, location: &Location) -> String {
+        match *location {
+            Location::FrameOffset { base, offset } => {
+                format!("{offset:#X}({})", self.frame_base_name(base))
+            }
+            Location::Register(reg) => format!("{}", self.gpr_name(reg)),
+        }
+    }
+}
+
+/// Name of a register in the naming of a target, written through `Display`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RegisterName {
+    prefix: &'static str,
+    number: u16,
+}
+
+impl fmt::Display for RegisterName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.prefix, self.number)
+    }
+}
+
+/// Options for decoding and encoding exception tables.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DecodeOptions {
+    pub endian: Endian,
+    pub target: Target,
+}
+
+impl DecodeOptions {
+    /// Returns the options for the given target, using its usual byte order.
+    pub fn for_target(target: Target) -> Self {
+        Self {
+            endian: target.default_endian(),
+            target,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn powerpc_names() {
+        let target = Target::PowerPc;
+        assert_eq!(target.gpr_name(3).to_string(), "r3");
+        assert_eq!(target.fpr_name(31).to_string(), "fp31");
+        assert_eq!(target.frame_base_name(FrameBase::Sp), "SP");
+        assert_eq!(target.frame_base_name(FrameBase::Fp), "FP");
+        assert_eq!(target.format_location(&Location::Register(30)), "r30");
+        let location = Location::FrameOffset {
+            base: FrameBase::Fp,
+            offset: 0x1C,
+        };
+        assert_eq!(target.format_location(&location), "0x1C(FP)");
+        //Matches the target-independent names
+        assert_eq!(target.format_location(&location), location.to_string());
+    }
+
+    #[test]
+    fn default_options() {
+        let options = DecodeOptions::default();
+        assert_eq!(options.endian, Endian::Big);
+        assert_eq!(options.target, Target::PowerPc);
+        assert_eq!(DecodeOptions::for_target(Target::PowerPc), options);
+    }
+}
@@ -0,0 +1,624 @@
+use alloc::collections::BTreeMap;
+use alloc::vec;
+use alloc::vec::Vec;
+use thiserror::Error;
+
+use crate::mem_utils;
+use crate::{decode_extab, Endian, ExceptionTableData, ExtabDecodeError, ExtabFlags};
+use crate::{ExtabIndexEntry, ModuleTarget};
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum RelError {
+    #[error("Truncated REL data: {0}")]
+    Truncated(ExtabDecodeError),
+    #[error("Unsupported REL version {0}")]
+    UnsupportedVersion(u32),
+    #[error("REL section {index} (0x{offset:X}, size 0x{size:X}) is outside of the file")]
+    InvalidSection { index: u32, offset: u32, size: u32 },
+    #[error("Relocation {index} of the imports for module {module_id} is not inside a section")]
+    RelocationOutsideSection { module_id: u32, index: u32 },
+    #[error("Could not find the .extab and .extabindex sections in the REL")]
+    ExtabNotFound,
+    #[error("REL section {0} doesn't exist or has no data in the file")]
+    MissingSectionData(u32),
+    #[error("Index entry {index} points outside of .extab (offset 0x{offset:X})")]
+    InvalidExtabOffset { index: u32, offset: u32 },
+    #[error("Index entry {index} has no relocation to its table and is not stored directly")]
+    MissingExtabRelocation { index: u32 },
+    #[error("Failed to decode the table of index entry {index}: {error}")]
+    Decode { index: u32, error: ExtabDecodeError },
+}
+
+/// Struct for a section of a REL file.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RelSection {
+    pub index: u32,
+    /// File offset of the section data, 0 for sections without data like .bss
+    pub file_offset: u32,
+    pub size: u32,
+    pub executable: bool,
+}
+
+/// Struct for an import of a REL file, which holds the relocations against a module.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RelImport {
+    /// ID of the module the relocations point to, 0 for the main executable
+    pub module_id: u32,
+    /// File offset of the relocation commands
+    pub offset: u32,
+}
+
+/// Struct for a relocation of a REL file, with the relocation commands resolved to the
+/// section and offset the relocation is applied to.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RelRelocation {
+    /// Section the relocation is applied to
+    pub section: u8,
+    /// Offset in the section the relocation is applied to
+    pub offset: u32,
+    /// ELF relocation type, like `R_PPC_ADDR32`
+    pub kind: u8,
+    pub target: ModuleTarget,
+}
+
+/// Struct for the exception table of a function in a REL file.
+#[derive(Debug, Clone)]
+pub struct RelExtab {
+    /// Section and offset of the function the table belongs to
+    pub function: ModuleTarget,
+    /// Raw function size value of the index entry, see `ExtabIndexEntry`
+    pub size_word: u32,
+    /// Offset of the table in the .extab section
+    pub extab_offset: u32,
+    pub table: ExceptionTableData,
+}
+
+/// Parsed REL (relocatable module) file.
+#[derive(Debug, Clone)]
+pub struct Rel<'a> {
+    data: &'a [u8],
+    pub module_id: u32,
+    pub version: u32,
+    sections: Vec<RelSection>,
+    imports: Vec<RelImport>,
+    relocations: Vec<RelRelocation>,
+}
+
+impl<'a> Rel<'a> {
+    pub const R_PPC_ADDR32: u8 = 1;
+    pub const R_DOLPHIN_NOP: u8 = 201;
+    pub const R_DOLPHIN_SECTION: u8 = 202;
+    pub const R_DOLPHIN_END: u8 = 203;
+    pub const R_DOLPHIN_MRKREF: u8 = 204;
+
+    /// Parses the header, section table, imports and relocations of the provided REL file.
+    pub fn parse(data: &'a [u8]) -> Result<Self, RelError> {
+        let read = |offset: u32, field| {
+            mem_utils::read_uint32(data, &mut (offset as usize), false, field, Endian::Big)
+                .map_err(RelError::Truncated)
+        };
+
+        let module_id = read(0x0, "id")?;
+        let num_sections = read(0xC, "num_sections")?;
+        let section_info_offset = read(0x10, "section_info_offset")?;
+        let version = read(0x1C, "version")?;
+        let imp_offset = read(0x28, "imp_offset")?;
+        let imp_size = read(0x2C, "imp_size")?;
+        if !(1..=3).contains(&version) {
+            return Err(RelError::UnsupportedVersion(version));
+        }
+
+        let mut sections = vec![];
+        for index in 0..num_sections {
+            let info_offset = section_info_offset.wrapping_add(index * 8);
+            let offset_word = read(info_offset, "section_offset")?;
+            let size = read(info_offset.wrapping_add(4), "section_size")?;
+            //Bit 0 of the offset marks executable sections
+            let file_offset = offset_word & !1;
+            if file_offset != 0 && file_offset as usize + size as usize > data.len() {
+                return Err(RelError::InvalidSection {
+                    index,
+                    offset: file_offset,
+                    size,
+                });
+            }
+            sections.push(RelSection {
+                index,
+                file_offset,
+                size,
+                executable: (offset_word & 1) != 0,
+            });
+        }
+
+        let mut imports = vec![];
+        for i in 0..imp_size / 8 {
+            let import_offset = imp_offset.wrapping_add(i * 8);
+            imports.push(RelImport {
+                module_id: read(import_offset, "import_module_id")?,
+                offset: read(import_offset.wrapping_add(4), "import_offset")?,
+            });
+        }
+
+        let mut relocations = vec![];
+        for import in &imports {
+            Self::parse_relocations(data, import, &mut relocations)?;
+        }
+
+        Ok(Self {
+            data,
+            module_id,
+            version,
+            sections,
+            imports,
+            relocations,
+        })
+    }
+
+    /// Runs the relocation commands of an import, and adds the relocations to the list.
+    fn parse_relocations(
+        data: &[u8],
+        import: &RelImport,
+        relocations: &mut Vec<RelRelocation>,
+    ) -> Result<(), RelError> {
+        let mut offset = import.offset as usize;
+        let mut section: Option<u8> = None;
+        let mut position: u32 = 0;
+        let mut index: u32 = 0;
+        loop {
+            let mut read_field = |size, field| -> Result<u32, RelError> {
+                let value = match size {
+                    1 => mem_utils::read_byte(data, &mut offset, true, field).map(u32::from),
+                    2 => mem_utils::read_uint16(data, &mut offset, true, field, Endian::Big)
+                        .map(u32::from),
+                    _ => mem_utils::read_uint32(data, &mut offset, true, field, Endian::Big),
+                };
+                value.map_err(RelError::Truncated)
+            };
+            //Each command moves the position from the previous one
+            let delta = read_field(2, "offset")?;
+            let kind = read_field(1, "type")? as u8;
+            let target_section = read_field(1, "section")? as u8;
+            let addend = read_field(4, "addend")?;
+            position = position.wrapping_add(delta);
+
+            match kind {
+                Self::R_DOLPHIN_END => break,
+                Self::R_DOLPHIN_SECTION => {
+                    section = Some(target_section);
+                    position = 0;
+                }
+                Self::R_DOLPHIN_NOP | Self::R_DOLPHIN_MRKREF => {}
+                _ => {
+                    let section = section.ok_or(RelError::RelocationOutsideSection {
+                        module_id: import.module_id,
+                        index,
+                    })?;
+                    relocations.push(RelRelocation {
+                        section,
+                        offset: position,
+                        kind,
+                        target: ModuleTarget {
+                            module_id: import.module_id,
+                            section: target_section,
+                            offset: addend,
+                        },
+                    });
+                }
+            }
+            index += 1;
+        }
+        Ok(())
+    }
+
+    /// Returns the sections of the REL, including empty ones.
+    pub fn sections(&self) -> &[RelSection] {
+        &self.sections
+    }
+
+    /// Returns the imports of the REL.
+    pub fn imports(&self) -> &[RelImport] {
+        &self.imports
+    }
+
+    /// Returns the relocations of all imports, in the order of the relocation commands.
+    pub fn relocations(&self) -> &[RelRelocation] {
+        &self.relocations
+    }
+
+    /// Returns the data of the section, or `None` if the section has no data in the file.
+    pub fn section_data(&self, index: u32) -> Option<&'a [u8]> {
+        let section = self.sections.get(index as usize)?;
+        if section.file_offset == 0 {
+            return None;
+        }
+        let start = section.file_offset as usize;
+        self.data.get(start..start + section.size as usize)
+    }
+
+    /// Returns the ADDR32 relocations applied to the section, by offset.
+    fn section_relocations(&self, section: u32) -> BTreeMap<u32, ModuleTarget> {
+        self.relocations
+            .iter()
+            .filter(|r| r.section as u32 == section && r.kind == Self::R_PPC_ADDR32)
+            .map(|r| (r.offset, r.target))
+            .collect()
+    }
+
+    /// Looks for the .extabindex and .extab sections, and returns their indices.
+    ///
+    /// Sections have no names in a REL, so .extabindex is found from its relocations: every
+    /// entry points to code, and the entries with a table all point to the same data section,
+    /// which is .extab.
+    pub fn find_extab_sections(&self) -> Option<(u32, u32)> {
+        let is_executable = |index: u8| {
+            self.sections
+                .get(index as usize)
+                .map_or(false, |section| section.executable)
+        };
+        for section in &self.sections {
+            let data = match self.section_data(section.index) {
+                Some(data) if !section.executable && !data.is_empty() => data,
+                _ => continue,
+            };
+            if data.len() % ExtabIndexEntry::SIZE != 0 {
+                continue;
+            }
+            let relocations = self.section_relocations(section.index);
+            let mut extab_section: Option<u8> = None;
+            let is_index = (0..data.len() / ExtabIndexEntry::SIZE).all(|i| {
+                let offset = (i * ExtabIndexEntry::SIZE) as u32;
+                let function = relocations.get(&offset);
+                let extab = relocations.get(&(offset + 8));
+                let points_to_code = function.map_or(false, |target| {
+                    target.module_id == self.module_id && is_executable(target.section)
+                });
+                let points_to_extab = match extab {
+                    Some(target) if target.module_id == self.module_id => {
+                        let section = *extab_section.get_or_insert(target.section);
+                        section == target.section && !is_executable(section)
+                    }
+                    Some(_) => false,
+                    None => true,
+                };
+                points_to_code && points_to_extab
+            });
+            if let (true, Some(extab_section)) = (is_index, extab_section) {
+                return Some((section.index, extab_section as u32));
+            }
+        }
+        None
+    }
+
+    /// Decodes the exception tables of the REL, using `find_extab_sections` to find them.
+    /// See `decode_tables_in`.
+    pub fn decode_tables(&self) -> Result<Vec<RelExtab>, RelError> {
+        let (index_section, extab_section) =
+            self.find_extab_sections().ok_or(RelError::ExtabNotFound)?;
+        self.decode_tables_in(index_section, extab_section)
+    }
+
+    /// Decodes the exception tables of the REL from the given .extabindex and .extab
+    /// sections.
+    ///
+    /// The dtor and type info addresses are zero in a REL, so each relocation of the
+    /// decoded tables gets its module, section and offset target from the REL relocations.
+    /// Relocations against the main executable also get their address.
+    pub fn decode_tables_in(
+        &self,
+        index_section: u32,
+        extab_section: u32,
+    ) -> Result<Vec<RelExtab>, RelError> {
+        let index_data = self
+            .section_data(index_section)
+            .ok_or(RelError::MissingSectionData(index_section))?;
+        let extab_data = self
+            .section_data(extab_section)
+            .ok_or(RelError::MissingSectionData(extab_section))?;
+        let index_relocations = self.section_relocations(index_section);
+        let extab_relocations = self.section_relocations(extab_section);
+
+        //Find the function and table offset of every index entry
+        let mut entries: Vec<(ModuleTarget, ExtabIndexEntry, Option<u32>)> = vec![];
+        for (i, entry) in index_data.chunks_exact(ExtabIndexEntry::SIZE).enumerate() {
+            let offset = (i * ExtabIndexEntry::SIZE) as u32;
+            //The function and table addresses come from the relocations
+            let index_entry = ExtabIndexEntry {
+                function_address: 0,
+                size_word: u32::from_be_bytes([entry[4], entry[5], entry[6], entry[7]]),
+                extab_address: u32::from_be_bytes([entry[8], entry[9], entry[10], entry[11]]),
+            };
+            let function = index_relocations
+                .get(&offset)
+                .copied()
+                .unwrap_or(ModuleTarget {
+                    module_id: self.module_id,
+                    section: 0,
+                    offset: 0,
+                });
+            let extab_offset = index_relocations.get(&(offset + 8)).map(|t| t.offset);
+            entries.push((function, index_entry, extab_offset));
+        }
+
+        //Tables are stored back to back, so each table ends where the next one starts
+        let mut starts: Vec<u32> = entries
+            .iter()
+            .filter(|entry| !entry.1.is_direct_store())
+            .filter_map(|entry| entry.2)
+            .collect();
+        starts.sort_unstable();
+        starts.dedup();
+
+        let mut tables = vec![];
+        for (i, (function, entry, extab_offset)) in entries.into_iter().enumerate() {
+            let index = i as u32;
+            let size_word = entry.size_word;
+            if entry.is_direct_store() {
+                let mut table = ExceptionTableData::new();
+                table.flags = ExtabFlags::from_bits(entry.extab_address);
+                tables.push(RelExtab {
+                    function,
+                    size_word,
+                    extab_offset: 0,
+                    table,
+                });
+                continue;
+            }
+            let extab_offset = extab_offset.ok_or(RelError::MissingExtabRelocation { index })?;
+            if extab_offset as usize >= extab_data.len() {
+                return Err(RelError::InvalidExtabOffset {
+                    index,
+                    offset: extab_offset,
+                });
+            }
+            let end = match starts.iter().find(|&&start| start > extab_offset) {
+                Some(&start) => start,
+                None => extab_data.len() as u32,
+            };
+            let data = &extab_data[extab_offset as usize..end as usize];
+            let mut table =
+                decode_extab(data).map_err(|error| RelError::Decode { index, error })?;
+
+            for relocation in &mut table.relocations {
+                if let Some(target) = extab_relocations.get(&(extab_offset + relocation.offset)) {
+                    relocation.target = Some(*target);
+                    if target.module_id == 0 {
+                        relocation.address = target.offset;
+                    }
+                }
+            }
+
+            tables.push(RelExtab {
+                function,
+                size_word,
+                extab_offset,
+                table,
+            });
+        }
+        Ok(tables)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODULE_ID: u32 = 5;
+    const SECTION_TABLE: u32 = 0x50;
+    const IMPORTS: u32 = 0xC8;
+
+    /// Relocation command: offset from the previous command, type, section and addend.
+    type Command = (u16, u8, u8, u32);
+
+    /// Index with a table in .extab and a directly stored table.
+    const INDEX: [u8; 24] = [
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00, 0x00, //Entry 0
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x11, 0x00, 0x08, 0x00, 0x00, //Entry 1
+    ];
+    const EXTAB: [u8; 24] = [
+        0x00, 0x00, 0x00, 0x00, //Header
+        0x00, 0x00, 0x00, 0x04, 0x00, 0x02, 0x00, 0x10, //PC action
+        0x00, 0x00, 0x00, 0x00, //Terminator
+        0x82, 0x00, 0x00, 0x08, 0x00, 0x00, 0x00, 0x00, //DestroyLocal
+    ];
+
+    /// Commands for the relocations against the module itself, for the function and table
+    /// of each index entry.
+    const SELF_COMMANDS: [Command; 5] = [
+        (0, Rel::R_DOLPHIN_SECTION, 2, 0),
+        (0x0, Rel::R_PPC_ADDR32, 1, 0x0),
+        (0x8, Rel::R_PPC_ADDR32, 3, 0x0),
+        (0x4, Rel::R_PPC_ADDR32, 1, 0x10),
+        (0, Rel::R_DOLPHIN_END, 0, 0),
+    ];
+    /// Commands for the relocations against the main executable, for the dtor.
+    const DOL_COMMANDS: [Command; 4] = [
+        (0, Rel::R_DOLPHIN_SECTION, 3, 0),
+        (0x14, Rel::R_DOLPHIN_NOP, 0, 0),
+        (0x0, Rel::R_PPC_ADDR32, 0, 0x80001000),
+        (0, Rel::R_DOLPHIN_END, 0, 0),
+    ];
+
+    fn write_u32(data: &mut [u8], offset: u32, value: u32) {
+        let offset = offset as usize;
+        data[offset..offset + 4].copy_from_slice(&value.to_be_bytes());
+    }
+
+    /// Builds a REL with an empty section 0, .text, .extabindex, .extab and .bss.
+    fn rel_file(version: u32, index: &[u8], self_commands: &[Command]) -> Vec<u8> {
+        let mut data = vec![0; IMPORTS as usize + 0x10];
+        write_u32(&mut data, 0x0, MODULE_ID);
+        write_u32(&mut data, 0xC, 5);
+        write_u32(&mut data, 0x10, SECTION_TABLE);
+        write_u32(&mut data, 0x1C, version);
+        write_u32(&mut data, 0x28, IMPORTS);
+        write_u32(&mut data, 0x2C, 0x10);
+
+        let sections = [(0x78 | 1, 0x20), (0x98, index.len()), (0xB0, EXTAB.len())];
+        for (i, (offset, size)) in sections.iter().enumerate() {
+            let info = SECTION_TABLE + (i as u32 + 1) * 8;
+            write_u32(&mut data, info, *offset);
+            write_u32(&mut data, info + 4, *size as u32);
+        }
+        write_u32(&mut data, SECTION_TABLE + 4 * 8 + 4, 0x10);
+        data[0x98..0x98 + index.len()].copy_from_slice(index);
+        data[0xB0..0xC8].copy_from_slice(&EXTAB);
+
+        for (i, (module_id, commands)) in [(MODULE_ID, self_commands), (0, &DOL_COMMANDS[..])]
+            .iter()
+            .enumerate()
+        {
+            let import = IMPORTS + i as u32 * 8;
+            write_u32(&mut data, import, *module_id);
+            let commands_offset = data.len() as u32;
+            write_u32(&mut data, import + 4, commands_offset);
+            for &(offset, kind, section, addend) in commands.iter() {
+                data.extend_from_slice(&offset.to_be_bytes());
+                data.extend_from_slice(&[kind, section]);
+                data.extend_from_slice(&addend.to_be_bytes());
+            }
+        }
+        data
+    }
+
+    #[test]
+    fn parses_versions() {
+        for version in 1..=3 {
+            let data = rel_file(version, &INDEX, &SELF_COMMANDS);
+            let rel = Rel::parse(&data).unwrap();
+            assert_eq!(rel.version, version);
+            assert_eq!(rel.module_id, MODULE_ID);
+            assert_eq!(rel.sections().len(), 5);
+            assert_eq!(
+                rel.sections()[1],
+                RelSection {
+                    index: 1,
+                    file_offset: 0x78,
+                    size: 0x20,
+                    executable: true,
+                }
+            );
+            assert_eq!(rel.section_data(3), Some(&EXTAB[..]));
+            //Sections without data in the file, like .bss
+            assert_eq!(rel.section_data(4), None);
+            assert_eq!(rel.section_data(5), None);
+            assert_eq!(rel.imports().len(), 2);
+            assert_eq!(rel.imports()[1].module_id, 0);
+        }
+        for version in [0, 4] {
+            let data = rel_file(version, &INDEX, &SELF_COMMANDS);
+            assert_eq!(
+                Rel::parse(&data).unwrap_err(),
+                RelError::UnsupportedVersion(version)
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_files() {
+        let data = rel_file(3, &INDEX, &SELF_COMMANDS);
+        assert!(matches!(
+            Rel::parse(&data[..0x20]),
+            Err(RelError::Truncated(_))
+        ));
+        let mut bad_section = data.clone();
+        write_u32(&mut bad_section, SECTION_TABLE + 3 * 8 + 4, 0x1000);
+        assert_eq!(
+            Rel::parse(&bad_section).unwrap_err(),
+            RelError::InvalidSection {
+                index: 3,
+                offset: 0xB0,
+                size: 0x1000,
+            }
+        );
+        //Relocations must come after a section command
+        let data = rel_file(3, &INDEX, &SELF_COMMANDS[1..]);
+        assert_eq!(
+            Rel::parse(&data).unwrap_err(),
+            RelError::RelocationOutsideSection {
+                module_id: MODULE_ID,
+                index: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn parses_relocations() {
+        let data = rel_file(3, &INDEX, &SELF_COMMANDS);
+        let rel = Rel::parse(&data).unwrap();
+        let relocations: Vec<(u8, u32, ModuleTarget)> = rel
+            .relocations()
+            .iter()
+            .map(|r| (r.section, r.offset, r.target))
+            .collect();
+        let target = |module_id, section, offset| ModuleTarget {
+            module_id,
+            section,
+            offset,
+        };
+        //Offsets add up from the previous command, and section commands reset them
+        assert_eq!(
+            relocations,
+            vec![
+                (2, 0x0, target(MODULE_ID, 1, 0x0)),
+                (2, 0x8, target(MODULE_ID, 3, 0x0)),
+                (2, 0xC, target(MODULE_ID, 1, 0x10)),
+                (3, 0x14, target(0, 0, 0x80001000)),
+            ]
+        );
+        assert!(rel
+            .relocations()
+            .iter()
+            .all(|r| r.kind == Rel::R_PPC_ADDR32));
+    }
+
+    #[test]
+    fn decodes_tables() {
+        let data = rel_file(3, &INDEX, &SELF_COMMANDS);
+        let rel = Rel::parse(&data).unwrap();
+        assert_eq!(rel.find_extab_sections(), Some((2, 3)));
+        let tables = rel.decode_tables().unwrap();
+        assert_eq!(tables.len(), 2);
+        assert_eq!(
+            tables[0].function,
+            ModuleTarget {
+                module_id: MODULE_ID,
+                section: 1,
+                offset: 0,
+            }
+        );
+        let relocation = &tables[0].table.relocations[0];
+        assert_eq!(relocation.address, 0x80001000);
+        assert_eq!(relocation.target.map(|t| t.module_id), Some(0));
+
+        //The second table is stored in the index entry
+        assert_eq!(tables[1].function.offset, 0x10);
+        assert_eq!(tables[1].size_word, 0x11);
+        assert_eq!(tables[1].table.flags.bits(), 0x00080000);
+    }
+
+    #[test]
+    fn rejects_invalid_tables() {
+        let data = rel_file(3, &INDEX, &SELF_COMMANDS);
+        let rel = Rel::parse(&data).unwrap();
+        assert_eq!(
+            rel.decode_tables_in(2, 4).unwrap_err(),
+            RelError::MissingSectionData(4)
+        );
+        assert_eq!(
+            rel.decode_tables_in(9, 3).unwrap_err(),
+            RelError::MissingSectionData(9)
+        );
+
+        //The first entry without the relocation to its table
+        let mut commands = SELF_COMMANDS;
+        commands[2] = (0x8, Rel::R_DOLPHIN_NOP, 0, 0);
+        let data = rel_file(3, &INDEX, &commands);
+        let rel = Rel::parse(&data).unwrap();
+        assert_eq!(rel.find_extab_sections(), None);
+        assert_eq!(rel.decode_tables().unwrap_err(), RelError::ExtabNotFound);
+        assert_eq!(
+            rel.decode_tables_in(2, 3).unwrap_err(),
+            RelError::MissingExtabRelocation { index: 0 }
+        );
+    }
+}
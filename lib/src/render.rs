@@ -0,0 +1,731 @@
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
+
+use crate::{CatchType, ExAction, ExActionData, ExceptionAction, ExceptionTableData};
+use crate::{Location, NoSymbols, RelocationField, SymbolResolver, Target};
+
+/// Letter case of hex values in the text output.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum HexCase {
+    Upper,
+    Lower,
+}
+
+impl Default for HexCase {
+    fn default() -> Self {
+        HexCase::Upper
+    }
+}
+
+/// Number format of offsets in the text output.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum OffsetStyle {
+    Hex,
+    Decimal,
+}
+
+impl Default for OffsetStyle {
+    fn default() -> Self {
+        OffsetStyle::Hex
+    }
+}
+
+/// Indentation of the text output. Entries are indented one level below their section
+/// header, and the fields of each action two levels.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum IndentStyle {
+    None,
+    /// The given number of spaces per level
+    Spaces(u8),
+    /// One tab per level
+    Tab,
+}
+
+impl Default for IndentStyle {
+    fn default() -> Self {
+        IndentStyle::None
+    }
+}
+
+/// Options for the text output of exception tables.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct FormatOptions {
+    pub hex_case: HexCase,
+    /// Format of table offsets, PC offsets and frame and member offsets. Addresses are
+    /// always written in hex.
+    pub offsets: OffsetStyle,
+    /// Whether to write the raw bytes of each action next to its offset
+    pub raw_bytes: bool,
+    /// Whether to leave out the values with no known meaning: the `unk0`, `unk4` and `unk8`
+    /// fields, the ET field and unknown bits of the header, and the raw action params
+    pub omit_unknown_fields: bool,
+    pub indent: IndentStyle,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            hex_case: HexCase::Upper,
+            offsets: OffsetStyle::Hex,
+            raw_bytes: false,
+            omit_unknown_fields: true,
+            indent: IndentStyle::None,
+        }
+    }
+}
+
+impl FormatOptions {
+    /// Returns an address or raw value, as hex padded to the given width.
+    fn hex(&self, value: u32, width: usize) -> Number {
+        Number {
+            value,
+            width,
+            hex: true,
+            prefix: false,
+            case: self.hex_case,
+        }
+    }
+
+    /// Returns a table or PC offset, padded to the given width if written as hex.
+    fn offset(&self, value: u32, width: usize) -> Number {
+        Number {
+            hex: self.offsets == OffsetStyle::Hex,
+            ..self.hex(value, width)
+        }
+    }
+
+    /// Returns a frame or member offset, with a `0x` prefix if written as hex.
+    fn frame_offset(&self, value: u32) -> Number {
+        Number {
+            prefix: true,
+            ..self.offset(value, 0)
+        }
+    }
+}
+
+/// Number written with the format options.
+#[derive(Debug, Copy, Clone)]
+struct Number {
+    value: u32,
+    width: usize,
+    hex: bool,
+    prefix: bool,
+    case: HexCase,
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = self.value;
+        let width = self.width;
+        match (self.hex, self.prefix, self.case) {
+            (false, _, _) => write!(f, "{value}"),
+            (true, false, HexCase::Upper) => write!(f, "{value:0width$X}"),
+            (true, false, HexCase::Lower) => write!(f, "{value:0width$x}"),
+            (true, true, HexCase::Upper) => write!(f, "{value:#X}"),
+            (true, true, HexCase::Lower) => write!(f, "{value:#x}"),
+        }
+    }
+}
+
+/// Operand location written with the register names of the target, optionally as a member
+/// of the object it points to.
+struct LocationText {
+    location: Location,
+    member_offset: Option<u32>,
+    target: Target,
+    options: FormatOptions,
+}
+
+impl fmt::Display for LocationText {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let options = &self.options;
+        match self.location {
+            Location::Register(reg) => match self.member_offset {
+                Some(member) => write!(
+                    f,
+                    "{}({})",
+                    options.frame_offset(member),
+                    self.target.gpr_name(reg)
+                ),
+                None => self.target.gpr_name(reg).fmt(f),
+            },
+            Location::FrameOffset { base, offset } => {
+                write!(
+                    f,
+                    "{}({})",
+                    options.frame_offset(offset),
+                    self.target.frame_base_name(base)
+                )?;
+                if let Some(member) = self.member_offset {
+                    write!(f, "+{}", options.frame_offset(member))?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Writes an exception table as text, naming the dtors and catch types with the given
+/// resolver. A list of names is matched to the dtors in order.
+///
+/// The text is written through `Display`, so it can go straight into any `fmt::Write`, or
+/// be converted with `to_string`.
+#[derive(Debug, Clone)]
+pub struct Renderer<'a, R> {
+    table: &'a ExceptionTableData,
+    symbols: R,
+    options: FormatOptions,
+}
+
+impl<'a, R: SymbolResolver> Renderer<'a, R> {
+    pub fn new(table: &'a ExceptionTableData, symbols: R) -> Self {
+        Self::with_options(table, symbols, FormatOptions::default())
+    }
+
+    pub fn with_options(table: &'a ExceptionTableData, symbols: R, options: FormatOptions) -> Self {
+        Self {
+            table,
+            symbols,
+            options,
+        }
+    }
+
+    fn indent(&self, f: &mut fmt::Formatter<'_>, level: usize) -> fmt::Result {
+        for _ in 0..level {
+            match self.options.indent {
+                IndentStyle::None => {}
+                IndentStyle::Spaces(count) => {
+                    for _ in 0..count {
+                        f.write_char(' ')?;
+                    }
+                }
+                IndentStyle::Tab => f.write_char('\t')?,
+            }
+        }
+        Ok(())
+    }
+
+    fn line(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        level: usize,
+        args: fmt::Arguments<'_>,
+    ) -> fmt::Result {
+        self.indent(f, level)?;
+        f.write_fmt(args)?;
+        f.write_char('\n')
+    }
+
+    /// Writes a field holding a location, if the action has one.
+    fn location_line(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        name: &str,
+        location: Option<Location>,
+        member_offset: Option<u32>,
+    ) -> fmt::Result {
+        match location {
+            Some(location) => {
+                let text = LocationText {
+                    location,
+                    member_offset,
+                    target: self.table.options.target,
+                    options: self.options,
+                };
+                self.line(f, 2, format_args!("{name}: {text}"))
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn write_flags(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let flags = &self.table.flags;
+        let target = self.table.options.target;
+        let yes_no = |value: bool| if value { "Yes" } else { "No" };
+
+        f.write_str("Flag values:\n")?;
+        self.line(
+            f,
+            1,
+            format_args!("Has Elf Vector: {}", yes_no(flags.has_elf_vector())),
+        )?;
+        self.line(
+            f,
+            1,
+            format_args!("Large Frame: {}", yes_no(flags.large_frame())),
+        )?;
+        self.line(
+            f,
+            1,
+            format_args!("Has Frame Pointer: {}", yes_no(flags.has_frame_pointer())),
+        )?;
+        self.line(f, 1, format_args!("Saved CR: {}", yes_no(flags.saved_cr())))?;
+
+        if flags.fpr_save_count() != 0 {
+            let start_fpr = *flags.saved_fprs().start();
+            if start_fpr == 31 {
+                self.line(
+                    f,
+                    1,
+                    format_args!("Saved FPR range: {}", target.fpr_name(31)),
+                )?;
+            } else {
+                self.line(
+                    f,
+                    1,
+                    format_args!(
+                        "Saved FPR range: {}-{}",
+                        target.fpr_name(start_fpr),
+                        target.fpr_name(31)
+                    ),
+                )?;
+            }
+        }
+        if flags.gpr_save_count() != 0 {
+            let start_gpr = *flags.saved_gprs().start();
+            if start_gpr == 31 {
+                self.line(
+                    f,
+                    1,
+                    format_args!("Saved GPR range: {}", target.gpr_name(31)),
+                )?;
+            } else {
+                self.line(
+                    f,
+                    1,
+                    format_args!(
+                        "Saved GPR range: {}-{}",
+                        target.gpr_name(start_gpr.into()),
+                        target.gpr_name(31)
+                    ),
+                )?;
+            }
+        }
+        if !self.options.omit_unknown_fields {
+            let options = &self.options;
+            let et_field = flags.et_field() as u32;
+            self.line(f, 1, format_args!("ET field: {}", options.hex(et_field, 4)))?;
+            let unknown_bits = flags.unknown_bits() as u32;
+            self.line(
+                f,
+                1,
+                format_args!("Unknown bits: {}", options.hex(unknown_bits, 4)),
+            )?;
+        }
+        f.write_char('\n')
+    }
+
+    fn write_pc_actions(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let options = &self.options;
+        f.write_str("PC actions:\n")?;
+        for pcaction in &self.table.pc_actions {
+            let start_pc = options.offset(pcaction.start_pc, 8);
+            let end_pc = options.offset(pcaction.end_pc, 8);
+            let action_offset = options.offset(pcaction.action_offset, 6);
+            if pcaction.start_pc != pcaction.end_pc {
+                self.line(
+                    f,
+                    1,
+                    format_args!("PC={start_pc}:{end_pc}, Action: {action_offset}"),
+                )?;
+            } else {
+                self.line(f, 1, format_args!("PC={start_pc}, Action: {action_offset}"))?;
+            }
+        }
+        f.write_char('\n')
+    }
+
+    fn write_action(&self, f: &mut fmt::Formatter<'_>, action: &ExceptionAction) -> fmt::Result {
+        let options = &self.options;
+
+        //Write the offset, followed by the raw bytes of the entry if enabled
+        self.indent(f, 1)?;
+        write!(f, "{}:", options.offset(action.action_offset, 6))?;
+        if options.raw_bytes {
+            let type_byte = action.action_type.to_int() as u32 | ((action.has_end_bit as u32) << 7);
+            write!(f, " {}", options.hex(type_byte, 2))?;
+            write!(f, " {}", options.hex(action.action_param as u32, 2))?;
+            for &byte in &action.bytes {
+                write!(f, " {}", options.hex(byte as u32, 2))?;
+            }
+        }
+        f.write_char('\n')?;
+        let action_name = ExAction::ACTION_NAMES[action.action_type.to_int() as usize];
+        self.line(f, 2, format_args!("Type: {action_name}"))?;
+        if !options.omit_unknown_fields {
+            let param = action.action_param as u32;
+            self.line(f, 2, format_args!("Param: {}", options.hex(param, 2)))?;
+        }
+        self.write_action_fields(f, action)?;
+
+        if action.has_end_bit {
+            self.line(f, 2, format_args!("Has end bit"))?;
+        }
+        Ok(())
+    }
+
+    /// Writes the operands of an action, and the names of its dtor and catch type.
+    fn write_action_fields(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        action: &ExceptionAction,
+    ) -> fmt::Result {
+        let options = &self.options;
+        let table = self.table;
+
+        match action.get_exaction_data_with_options(self.table.options) {
+            Ok(exaction_data) => self.write_action_data(f, action, &exaction_data)?,
+            Err(error) => self.line(f, 2, format_args!("Invalid data: {error}"))?,
+        }
+
+        //If the action references a dtor, write its name, or the address if unknown
+        if action.has_dtor_ref() {
+            match table.action_symbol(action, RelocationField::Dtor, &self.symbols) {
+                Some(Ok(func_name)) => self.line(f, 2, format_args!("Dtor: \"{func_name}\""))?,
+                Some(Err(address)) => {
+                    self.line(f, 2, format_args!("Dtor: {}", options.hex(address, 8)))?
+                }
+                None => {}
+            }
+        }
+        if let Some(Ok(type_name)) =
+            table.action_symbol(action, RelocationField::CatchType, &self.symbols)
+        {
+            self.line(f, 2, format_args!("catch_type: \"{type_name}\""))?;
+        }
+        Ok(())
+    }
+
+    fn write_action_data(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        action: &ExceptionAction,
+        exaction_data: &ExActionData,
+    ) -> fmt::Result {
+        let options = &self.options;
+        let base = self.table.frame_base();
+        let object = exaction_data.object_location(action.action_param, base);
+        let condition = exaction_data.condition_location(action.action_param, base);
+        let mut unknown: Option<(&str, u16)> = None;
+
+        match *exaction_data {
+            ExActionData::EndOfList | ExActionData::Terminate => {}
+            ExActionData::Branch { target_offset } => {
+                let target_offset = options.offset(target_offset as u32, 6);
+                self.line(f, 2, format_args!("Action: {target_offset}"))?;
+            }
+            ExActionData::DestroyLocal { .. } | ExActionData::ActiveCatchBlock { .. } => {
+                self.location_line(f, "Local", object, None)?;
+            }
+            ExActionData::DestroyLocalCond { unk4, .. } => {
+                self.location_line(f, "Local", object, None)?;
+                self.location_line(f, "Cond", condition, None)?;
+                unknown = Some(("unk4", unk4));
+            }
+            ExActionData::DestroyLocalPointer { .. } | ExActionData::DeletePointer { .. } => {
+                self.location_line(f, "Pointer", object, None)?;
+            }
+            ExActionData::DestroyLocalArray {
+                elements,
+                element_size,
+                ..
+            } => {
+                self.location_line(f, "Array", object, None)?;
+                self.line(f, 2, format_args!("Elements: {elements}"))?;
+                self.line(f, 2, format_args!("Size: {element_size}"))?;
+            }
+            ExActionData::DestroyBase { member_offset, .. }
+            | ExActionData::DestroyMember { member_offset, .. } => {
+                self.location_line(f, "Member", object, Some(member_offset))?;
+            }
+            ExActionData::DestroyMemberCond {
+                member_offset,
+                unk8,
+                ..
+            } => {
+                self.location_line(f, "Member", object, Some(member_offset))?;
+                self.location_line(f, "Cond", condition, None)?;
+                unknown = Some(("unk8", unk8));
+            }
+            ExActionData::DestroyMemberArray {
+                member_offset,
+                elements,
+                element_size,
+                ..
+            } => {
+                self.location_line(f, "Member", object, Some(member_offset))?;
+                self.line(f, 2, format_args!("Elements: {elements}"))?;
+                self.line(f, 2, format_args!("Size: {element_size}"))?;
+            }
+            ExActionData::DeletePointerCond { unk4, .. } => {
+                self.location_line(f, "Pointer", object, None)?;
+                self.location_line(f, "Cond", condition, None)?;
+                unknown = Some(("unk4", unk4));
+            }
+            ExActionData::CatchBlock {
+                unk0,
+                catch_type,
+                catch_pc_offset,
+                ..
+            } => {
+                self.write_catch_block(f, action, object, catch_type, catch_pc_offset as u32)?;
+                unknown = Some(("unk0", unk0));
+            }
+            ExActionData::CatchBlock32 {
+                unk0,
+                catch_type,
+                catch_pc_offset,
+                ..
+            } => {
+                self.write_catch_block(f, action, object, catch_type, catch_pc_offset)?;
+                unknown = Some(("unk0", unk0));
+            }
+            ExActionData::Specification {
+                specs,
+                pc_offset,
+                ref spec,
+                ..
+            } => {
+                self.location_line(f, "Local", object, None)?;
+                self.line(f, 2, format_args!("PC: {}", options.offset(pc_offset, 8)))?;
+                self.line(f, 2, format_args!("Types: {specs}"))?;
+                for &spec_type in spec {
+                    self.line(
+                        f,
+                        2,
+                        format_args!("spec_type_addr: {}", options.hex(spec_type, 8)),
+                    )?;
+                }
+
+                //Only write the type list if every type is known
+                let types: Option<Vec<CatchType>> = self
+                    .table
+                    .action_relocations(action, RelocationField::SpecType)
+                    .map(|relocation| self.symbols.resolve_type(relocation))
+                    .collect();
+                if let Some(types) = types.filter(|types| !types.is_empty()) {
+                    self.indent(f, 2)?;
+                    f.write_str("Throws: throw(")?;
+                    for (i, spec_type) in types.iter().enumerate() {
+                        if i != 0 {
+                            f.write_str(", ")?;
+                        }
+                        f.write_str(&spec_type.type_name())?;
+                    }
+                    f.write_str(")\n")?;
+                }
+            }
+        }
+
+        if let Some((name, value)) = unknown {
+            if !options.omit_unknown_fields {
+                self.line(
+                    f,
+                    2,
+                    format_args!("{name}: {}", options.hex(value as u32, 4)),
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_catch_block(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        action: &ExceptionAction,
+        object: Option<Location>,
+        catch_type: u32,
+        catch_pc_offset: u32,
+    ) -> fmt::Result {
+        let options = &self.options;
+        self.location_line(f, "Local", object, None)?;
+        self.line(
+            f,
+            2,
+            format_args!("PC: {}", options.offset(catch_pc_offset, 8)),
+        )?;
+        self.line(
+            f,
+            2,
+            format_args!("catch_type_addr: {}", options.hex(catch_type, 8)),
+        )?;
+        let catch_type = self
+            .table
+            .action_relocations(action, RelocationField::CatchType)
+            .next()
+            .and_then(|relocation| self.symbols.resolve_type(relocation));
+        if let Some(catch_type) = catch_type {
+            self.line(f, 2, format_args!("Catch: {}", catch_type.catch_clause()))?;
+        }
+        Ok(())
+    }
+}
+
+/// Operand and symbol lines of a single action, as written in the listing.
+pub(crate) struct ActionFields<'r, 'a, R> {
+    pub renderer: &'r Renderer<'a, R>,
+    pub action: &'r ExceptionAction,
+}
+
+impl<R: SymbolResolver> fmt::Display for ActionFields<'_, '_, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.renderer.write_action_fields(f, self.action)
+    }
+}
+
+impl<R: SymbolResolver> fmt::Display for Renderer<'_, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_flags(f)?;
+
+        //Write the exception range entries
+        if !self.table.pc_actions.is_empty() {
+            self.write_pc_actions(f)?;
+        }
+
+        if !self.table.exception_actions.is_empty() {
+            f.write_str("Exception actions:\n")?;
+            for action in &self.table.exception_actions {
+                self.write_action(f, action)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Writes the table with the default format options and without symbol names.
+impl fmt::Display for ExceptionTableData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Renderer::new(self, NoSymbols).fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ExtabBuilder, ExtabFlags};
+    use alloc::string::{String, ToString};
+
+    fn table() -> ExceptionTableData {
+        let mut builder = ExtabBuilder::new();
+        let mut flags = ExtabFlags::default();
+        flags.set_gpr_save_count(2).unwrap();
+        let label = builder.new_label();
+        builder.frame(flags).pc_range(0x1A0, 0x20, label);
+        builder
+            .begin_chain(label)
+            .destroy_local(0x2C, 0x8000ABCD)
+            .catch_block(0, 0x1E8, 0x18);
+        builder.build().unwrap()
+    }
+
+    fn render(options: FormatOptions) -> String {
+        table().render_with_options(NoSymbols, options).to_string()
+    }
+
+    #[test]
+    fn default_options() {
+        let text = render(FormatOptions::default());
+        assert_eq!(
+            text,
+            "Flag values:
+Has Elf Vector: No
+Large Frame: No
+Has Frame Pointer: No
+Saved CR: No
+Saved GPR range: r30-r31
+
+PC actions:
+PC=000001A0:000001C0, Action: 000010
+
+Exception actions:
+000010:
+Type: DESTROYLOCAL
+Local: 0x2C(SP)
+Dtor: 8000ABCD
+000018:
+Type: CATCHBLOCK (Small)
+Local: 0x18(SP)
+PC: 000001E8
+catch_type_addr: 00000000
+Has end bit
+"
+        );
+        assert_eq!(table().to_string(), text);
+    }
+
+    #[test]
+    fn lower_case_and_decimal_offsets() {
+        let text = render(FormatOptions {
+            hex_case: HexCase::Lower,
+            offsets: OffsetStyle::Decimal,
+            indent: IndentStyle::Spaces(2),
+            ..Default::default()
+        });
+        //Addresses stay in hex
+        assert_eq!(
+            text,
+            "Flag values:
+  Has Elf Vector: No
+  Large Frame: No
+  Has Frame Pointer: No
+  Saved CR: No
+  Saved GPR range: r30-r31
+
+PC actions:
+  PC=416:448, Action: 16
+
+Exception actions:
+  16:
+    Type: DESTROYLOCAL
+    Local: 44(SP)
+    Dtor: 8000abcd
+  24:
+    Type: CATCHBLOCK (Small)
+    Local: 24(SP)
+    PC: 488
+    catch_type_addr: 00000000
+    Has end bit
+"
+        );
+    }
+
+    #[test]
+    fn tabs_raw_bytes_and_unknown_fields() {
+        let text = render(FormatOptions {
+            raw_bytes: true,
+            omit_unknown_fields: false,
+            indent: IndentStyle::Tab,
+            ..Default::default()
+        });
+        assert_eq!(
+            text,
+            "Flag values:
+\tHas Elf Vector: No
+\tLarge Frame: No
+\tHas Frame Pointer: No
+\tSaved CR: No
+\tSaved GPR range: r30-r31
+\tET field: 0000
+\tUnknown bits: 0000
+
+PC actions:
+\tPC=000001A0:000001C0, Action: 000010
+
+Exception actions:
+\t000010: 02 00 00 2C 80 00 AB CD
+\t\tType: DESTROYLOCAL
+\t\tParam: 00
+\t\tLocal: 0x2C(SP)
+\t\tDtor: 8000ABCD
+\t000018: 8C 00 00 00 00 00 00 00 01 E8 00 18
+\t\tType: CATCHBLOCK (Small)
+\t\tParam: 00
+\t\tLocal: 0x18(SP)
+\t\tPC: 000001E8
+\t\tcatch_type_addr: 00000000
+\t\tunk0: 0000
+\t\tHas end bit
+"
+        );
+    }
+}
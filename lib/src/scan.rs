@@ -0,0 +1,272 @@
+use alloc::collections::BTreeSet;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::mem_utils;
+use crate::view::{self, ExceptionActionRef};
+use crate::ExceptionTableData;
+use crate::{decode_extab_with_options, DecodeOptions, ExActionData, ExtabFlags};
+
+/// Struct for an exception table found by the scanner.
+#[derive(Debug, Clone)]
+pub struct ExtabScanHit {
+    /// Offset of the table in the scanned data
+    pub offset: usize,
+    /// Size of the table in bytes, without padding
+    pub size: usize,
+    pub table: ExceptionTableData,
+    /// How likely the hit is to be a real table, from 0.0 to 1.0
+    pub score: f32,
+}
+
+/// Maximum number of PC actions read before a candidate is rejected.
+const MAX_PC_ACTIONS: usize = 1024;
+
+/// Scans the provided data for exception tables, like a memory dump without section
+/// headers. See `scan_extab_with_options`.
+pub fn scan_extab(data: &[u8]) -> Vec<ExtabScanHit> {
+    scan_extab_with_options(data, DecodeOptions::default())
+}
+
+/// Scans the provided data for exception tables using the given byte order and target.
+///
+/// Every 4 byte aligned offset is checked for a table with valid header flags, at least one
+/// PC action with increasing, non-overlapping ranges, a zero terminator, and action chains
+/// that fully decode and end with an end bit action or a branch. Candidates must also pass
+/// `decode_extab`. The hits are returned in offset order, and the data of a hit is not
+/// scanned again. Since any data can look like a table by chance, hits should be filtered
+/// by their score.
+pub fn scan_extab_with_options(data: &[u8], options: DecodeOptions) -> Vec<ExtabScanHit> {
+    let mut hits = vec![];
+    let mut offset: usize = 0;
+    while offset + 8 <= data.len() {
+        match check_candidate(&data[offset..], options) {
+            Some((size, table, score)) => {
+                hits.push(ExtabScanHit {
+                    offset,
+                    size,
+                    table,
+                    score,
+                });
+                offset += (size + 3) & !3;
+            }
+            None => offset += 4,
+        }
+    }
+    hits
+}
+
+/// Checks whether a table starts at the beginning of the data, and returns its size, the
+/// decoded table and its score if so.
+fn check_candidate(
+    data: &[u8],
+    options: DecodeOptions,
+) -> Option<(usize, ExceptionTableData, f32)> {
+    let endian = options.endian;
+    let mut offset: usize = 0;
+    let flag_val = mem_utils::read_uint16(data, &mut offset, true, "flags", endian).ok()?;
+    let et_field = mem_utils::read_uint16(data, &mut offset, true, "et_field", endian).ok()?;
+    let flags = ExtabFlags::from_raw(flag_val, et_field);
+    flags.validate().ok()?;
+
+    //The PC ranges must be sorted and not overlap
+    let mut pc_actions = vec![];
+    let mut previous_end: u32 = 0;
+    while let Some(pcaction) = view::read_pc_action(data, &mut offset, endian).ok()? {
+        if pcaction.end_pc < pcaction.start_pc
+            || pcaction.start_pc < previous_end
+            || pc_actions.len() >= MAX_PC_ACTIONS
+        {
+            return None;
+        }
+        previous_end = pcaction.end_pc;
+        pc_actions.push(pcaction);
+    }
+    if pc_actions.is_empty() {
+        return None;
+    }
+    offset += 4; //Skip the terminator
+    let actions_start = offset as u32;
+
+    //Read actions until every referenced action has been read and a chain has ended
+    let mut references: BTreeSet<u32> = pc_actions.iter().map(|a| a.action_offset).collect();
+    let mut actions: Vec<ExceptionActionRef> = vec![];
+    loop {
+        let action = ExceptionActionRef::read(data, &mut offset, options).ok()?;
+        //Branches end their chain without the end bit
+        let ends_chain = match action.get_exaction_data_with_options(options).ok()? {
+            ExActionData::Branch { target_offset } => {
+                references.insert(target_offset as u32);
+                true
+            }
+            _ => action.has_end_bit,
+        };
+        actions.push(action);
+        let last_reference = references.iter().next_back().copied().unwrap_or(0);
+        if ends_chain && offset as u32 > last_reference {
+            break;
+        }
+    }
+    let size = offset;
+
+    //Every reference has to point at the start of an action
+    let starts: BTreeSet<u32> = actions.iter().map(|a| a.action_offset).collect();
+    if references
+        .iter()
+        .any(|offset| *offset < actions_start || !starts.contains(offset))
+    {
+        return None;
+    }
+
+    let table = decode_extab_with_options(&data[..size], options).ok()?;
+    let score = score_candidate(&table, &actions, data, size);
+    Some((size, table, score))
+}
+
+/// Scores a candidate table from 0.0 to 1.0, from the traits that random data rarely has.
+fn score_candidate(
+    table: &ExceptionTableData,
+    actions: &[ExceptionActionRef],
+    data: &[u8],
+    size: usize,
+) -> f32 {
+    let mut score: f32 = 0.0;
+
+    //Flag bits with no known meaning are never set by the compiler
+    if table.flags.unknown_bits() == 0 {
+        score += 0.15;
+    }
+
+    //PC ranges are offsets of instructions in the function
+    if table.pc_actions.iter().all(|a| a.start_pc % 4 == 0) {
+        score += 0.15;
+    }
+
+    //More PC ranges make a chance match less likely
+    score += 0.2 * (table.pc_actions.len().min(4) as f32 / 4.0);
+
+    //Every action should be reachable from a PC action
+    let mut reached = vec![false; actions.len()];
+    for pcaction in &table.pc_actions {
+        let mut index = actions
+            .iter()
+            .position(|a| a.action_offset == pcaction.action_offset);
+        while let Some(i) = index {
+            if reached[i] {
+                break;
+            }
+            reached[i] = true;
+            let action = &actions[i];
+            index = match action.get_exaction_data_with_options(table.options) {
+                Ok(ExActionData::Branch { target_offset }) => actions
+                    .iter()
+                    .position(|a| a.action_offset == target_offset as u32),
+                _ if action.has_end_bit => None,
+                _ => Some(i + 1).filter(|&next| next < actions.len()),
+            };
+        }
+    }
+    if reached.iter().all(|&r| r) {
+        score += 0.2;
+    }
+
+    //Dtors are functions, so their addresses are aligned and not null in linked data
+    let dtors: Vec<u32> = actions
+        .iter()
+        .filter_map(|a| {
+            a.get_dtor_relocation_with_options(table.options)
+                .map(|(_, address)| address)
+        })
+        .collect();
+    if dtors
+        .iter()
+        .all(|&address| address != 0 && address % 4 == 0)
+    {
+        score += 0.2;
+    }
+
+    //Tables are 4 byte aligned, so the padding after the table should be zero
+    let padding_end = ((size + 3) & !3).min(data.len());
+    if data[size..padding_end].iter().all(|&b| b == 0) {
+        score += 0.1;
+    }
+    score
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{encode_extab, Endian, ExtabBuilder, Target};
+
+    fn table_bytes(options: DecodeOptions, ranges: &[(u32, u32)]) -> Vec<u8> {
+        let mut builder = ExtabBuilder::with_options(options);
+        let first = builder.new_label();
+        builder
+            .begin_chain(first)
+            .destroy_local(0x8, 0x80001000)
+            .destroy_local(0xC, 0x80001004);
+        for &(start_pc, length) in ranges {
+            builder.pc_range(start_pc, length, first);
+        }
+        encode_extab(&builder.build().unwrap()).unwrap()
+    }
+
+    /// Places the tables in the buffer at the given offsets, with filler bytes in between.
+    fn embed(tables: &[(usize, &[u8])], size: usize) -> Vec<u8> {
+        let mut data = vec![0xFF; size];
+        for &(offset, table) in tables {
+            data[offset..offset + table.len()].copy_from_slice(table);
+            let padding_end = (offset + table.len() + 3) & !3;
+            data[offset + table.len()..padding_end].fill(0);
+        }
+        data
+    }
+
+    #[test]
+    fn finds_embedded_tables() {
+        for endian in [Endian::Big, Endian::Little] {
+            let options = DecodeOptions {
+                endian,
+                target: Target::PowerPc,
+            };
+            let first = table_bytes(options, &[(0x10, 0x20)]);
+            let second = table_bytes(options, &[(0x8, 0x4), (0x14, 0x30)]);
+            let data = embed(&[(0x20, &first), (0x80, &second)], 0x100);
+
+            let hits = scan_extab_with_options(&data, options);
+            assert_eq!(hits.len(), 2);
+            assert_eq!((hits[0].offset, hits[0].size), (0x20, first.len()));
+            assert_eq!((hits[1].offset, hits[1].size), (0x80, second.len()));
+            assert_eq!(encode_extab(&hits[1].table).unwrap(), second);
+            assert!(hits.iter().all(|hit| hit.score > 0.5));
+        }
+    }
+
+    #[test]
+    fn finds_empty_pc_ranges() {
+        let table = table_bytes(DecodeOptions::default(), &[(0x10, 0), (0x18, 0x8)]);
+        let data = embed(&[(0x8, &table)], 0x80);
+        let hits = scan_extab(&data);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].offset, 0x8);
+        assert_eq!(
+            hits[0].table.pc_actions[0].start_pc,
+            hits[0].table.pc_actions[0].end_pc
+        );
+    }
+
+    #[test]
+    fn rejects_overlapping_pc_ranges() {
+        let table = table_bytes(DecodeOptions::default(), &[(0x10, 0x20), (0x18, 0x8)]);
+        let data = embed(&[(0x8, &table)], 0x80);
+        //The data of the rejected table can still look like a table by chance
+        assert!(scan_extab(&data).iter().all(|hit| hit.offset != 0x8));
+    }
+
+    #[test]
+    fn rejects_truncated_tables() {
+        let table = table_bytes(DecodeOptions::default(), &[(0x10, 0x20)]);
+        assert!(scan_extab(&table[..table.len() - 2]).is_empty());
+        assert!(scan_extab(&[0xFF; 0x40]).is_empty());
+    }
+}
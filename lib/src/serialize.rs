@@ -0,0 +1,266 @@
+use alloc::vec::Vec;
+use serde::de::Error as _;
+use serde::ser::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+
+use crate::{validate_extab, DecodeOptions, ExtabEncodeError, ExtabFlags, PCAction, Relocation};
+use crate::{ExAction, ExActionData, ExceptionAction, ExceptionTableData};
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ExtabSerdeError {
+    #[error("Exception action at offset 0x{offset:X} has data that doesn't match its bytes")]
+    ActionDataMismatch { offset: u32 },
+    #[error("Invalid table: {0}")]
+    InvalidTable(ExtabEncodeError),
+}
+
+/// Serialized form of an exception action in a table, with both the raw bytes and the data
+/// decoded in the byte order of the table.
+#[derive(Serialize)]
+struct ActionRef<'a> {
+    action_offset: u32,
+    action_type: ExAction,
+    action_param: u8,
+    has_end_bit: bool,
+    bytes: &'a [u8],
+    data: ExActionData,
+}
+
+#[derive(Deserialize)]
+struct ActionSchema {
+    action_offset: u32,
+    action_type: ExAction,
+    action_param: u8,
+    has_end_bit: bool,
+    bytes: Vec<u8>,
+    data: ExActionData,
+}
+
+#[derive(Serialize)]
+struct TableRef<'a> {
+    options: &'a DecodeOptions,
+    flags: &'a ExtabFlags,
+    pc_actions: &'a [PCAction],
+    exception_actions: Vec<ActionRef<'a>>,
+    relocations: &'a [Relocation],
+}
+
+#[derive(Deserialize)]
+struct TableSchema {
+    options: DecodeOptions,
+    flags: ExtabFlags,
+    pc_actions: Vec<PCAction>,
+    exception_actions: Vec<ActionSchema>,
+    relocations: Vec<Relocation>,
+}
+
+/// Fails if the data of an action can't be decoded from its byte array.
+impl Serialize for ExceptionTableData {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut exception_actions = Vec::with_capacity(self.exception_actions.len());
+        for exaction in &self.exception_actions {
+            let data = exaction
+                .get_exaction_data_with_options(self.options)
+                .map_err(S::Error::custom)?;
+            exception_actions.push(ActionRef {
+                action_offset: exaction.action_offset,
+                action_type: exaction.action_type,
+                action_param: exaction.action_param,
+                has_end_bit: exaction.has_end_bit,
+                bytes: &exaction.bytes,
+                data,
+            });
+        }
+        TableRef {
+            options: &self.options,
+            flags: &self.flags,
+            pc_actions: &self.pc_actions,
+            exception_actions,
+            relocations: &self.relocations,
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Fails if the table can't be encoded or has inconsistent references (see
+/// `ExtabEncodeError`), or if the bytes of an action don't decode to its data.
+impl<'de> Deserialize<'de> for ExceptionTableData {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let table = TableSchema::deserialize(deserializer)?;
+        let mut exception_actions = Vec::with_capacity(table.exception_actions.len());
+        let mut action_data = Vec::with_capacity(table.exception_actions.len());
+        for action in table.exception_actions {
+            exception_actions.push(ExceptionAction {
+                action_offset: action.action_offset,
+                action_type: action.action_type,
+                action_param: action.action_param,
+                has_end_bit: action.has_end_bit,
+                bytes: action.bytes,
+            });
+            action_data.push(action.data);
+        }
+        let extab_data = ExceptionTableData {
+            options: table.options,
+            flags: table.flags,
+            pc_actions: table.pc_actions,
+            exception_actions,
+            relocations: table.relocations,
+        };
+        validate_table(&extab_data, &action_data).map_err(D::Error::custom)?;
+        Ok(extab_data)
+    }
+}
+
+fn validate_table(
+    extab_data: &ExceptionTableData,
+    action_data: &[ExActionData],
+) -> Result<(), ExtabSerdeError> {
+    validate_extab(extab_data).map_err(ExtabSerdeError::InvalidTable)?;
+    //A different action name in the data also ends up here, since the variants differ
+    for (exaction, data) in extab_data.exception_actions.iter().zip(action_data) {
+        match exaction.get_exaction_data_with_options(extab_data.options) {
+            Ok(decoded) if decoded == *data => {}
+            _ => {
+                return Err(ExtabSerdeError::ActionDataMismatch {
+                    offset: exaction.action_offset,
+                })
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{encode_extab, Endian, ExtabBuilder, FrameBase, Location, RelocationField, Target};
+    use alloc::string::{String, ToString};
+
+    fn sample_table(options: DecodeOptions) -> ExceptionTableData {
+        let mut builder = ExtabBuilder::with_options(options);
+        let first = builder.new_label();
+        let tail = builder.new_label();
+        builder
+            .begin_chain(first)
+            .destroy_local(0x8, 0)
+            .symbol("__dt__3FooFv")
+            .branch_to(tail)
+            .begin_chain(tail)
+            .delete_pointer_cond(
+                Location::Register(3),
+                Location::FrameOffset {
+                    base: FrameBase::Sp,
+                    offset: 0x10,
+                },
+                0x80001000,
+            )
+            .specification(0x20, 0x18, &[0x80002000]);
+        builder.pc_range(0x10, 0x20, first);
+        builder.build().unwrap()
+    }
+
+    /// Serializes the table, edits the JSON value and deserializes it again.
+    fn edit_json(
+        table: &ExceptionTableData,
+        edit: impl FnOnce(&mut serde_json::Value),
+    ) -> Result<ExceptionTableData, String> {
+        let mut value = serde_json::to_value(table).unwrap();
+        edit(&mut value);
+        serde_json::from_value(value).map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn json_round_trip() {
+        for endian in [Endian::Big, Endian::Little] {
+            let options = DecodeOptions {
+                endian,
+                target: Target::PowerPc,
+            };
+            let table = sample_table(options);
+            let json = serde_json::to_string(&table).unwrap();
+            let parsed: ExceptionTableData = serde_json::from_str(&json).unwrap();
+            assert_eq!(parsed.options, options);
+            assert_eq!(encode_extab(&parsed), encode_extab(&table));
+            assert_eq!(
+                parsed.relocations[0].symbol.as_deref(),
+                Some("__dt__3FooFv")
+            );
+        }
+    }
+
+    #[test]
+    fn json_has_decoded_data() {
+        let value = serde_json::to_value(sample_table(DecodeOptions::default())).unwrap();
+        let data = &value["exception_actions"][0]["data"];
+        assert_eq!(data["action"], "DestroyLocal");
+        assert_eq!(data["local_offset"], 8);
+    }
+
+    #[test]
+    fn rejects_data_mismatch() {
+        let table = sample_table(DecodeOptions::default());
+        let error = edit_json(&table, |value| {
+            value["exception_actions"][0]["data"]["local_offset"] = 0xC.into();
+        })
+        .unwrap_err();
+        let expected = ExtabSerdeError::ActionDataMismatch { offset: 0x10 };
+        assert!(error.starts_with(&expected.to_string()));
+    }
+
+    #[test]
+    fn rejects_invalid_data_size() {
+        let table = sample_table(DecodeOptions::default());
+        let error = edit_json(&table, |value| {
+            value["exception_actions"][0]["bytes"]
+                .as_array_mut()
+                .unwrap()
+                .pop();
+        })
+        .unwrap_err();
+        assert!(error.contains("has 5 bytes of data, but its action type needs 6"));
+    }
+
+    #[test]
+    fn rejects_pc_action_without_action() {
+        let table = sample_table(DecodeOptions::default());
+        let error = edit_json(&table, |value| {
+            value["pc_actions"][0]["action_offset"] = 0x12.into();
+        })
+        .unwrap_err();
+        let expected = ExtabSerdeError::InvalidTable(ExtabEncodeError::UnknownActionOffset {
+            index: 0,
+            action_offset: 0x12,
+        });
+        assert!(error.starts_with(&expected.to_string()));
+    }
+
+    #[test]
+    fn rejects_overlapping_actions() {
+        let table = sample_table(DecodeOptions::default());
+        let error = edit_json(&table, |value| {
+            value["exception_actions"][1]["action_offset"] = 0x14.into();
+        })
+        .unwrap_err();
+        let expected = ExtabSerdeError::InvalidTable(ExtabEncodeError::ActionOffsetMismatch {
+            index: 1,
+            expected: 0x18,
+            actual: 0x14,
+        });
+        assert!(error.starts_with(&expected.to_string()));
+    }
+
+    #[test]
+    fn rejects_relocation_of_wrong_field() {
+        let table = sample_table(DecodeOptions::default());
+        let error = edit_json(&table, |value| {
+            value["relocations"][0]["field"] = "CatchType".into();
+        })
+        .unwrap_err();
+        let expected = ExtabSerdeError::InvalidTable(ExtabEncodeError::InvalidRelocationField {
+            offset: 0x14,
+            field: RelocationField::CatchType,
+        });
+        assert!(error.starts_with(&expected.to_string()));
+    }
+}
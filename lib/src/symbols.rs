@@ -0,0 +1,175 @@
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::{CatchType, MapFile, Relocation, RelocationField};
+
+/// Trait for looking up the names of the symbols referenced by a table, like the dtors and
+/// type infos.
+pub trait SymbolResolver {
+    /// Returns the name of the symbol at the given address, if known.
+    fn resolve_address(&self, address: u32) -> Option<String>;
+
+    /// Returns the name of the target of a relocation. `index` is the position of the
+    /// relocation among the relocations of the same field in the table, so the first dtor
+    /// is 0, the second dtor is 1 and so on.
+    ///
+    /// By default, the symbol already stored in the relocation is used, and otherwise the
+    /// address is looked up.
+    fn resolve_relocation(&self, index: usize, relocation: &Relocation) -> Option<String> {
+        let _ = index;
+        match &relocation.symbol {
+            Some(symbol) => Some(symbol.clone()),
+            None => self.resolve_address(relocation.address),
+        }
+    }
+
+    /// Returns the type a catch type or spec type relocation points at, if known. See
+    /// `TypeInfoResolver`.
+    fn resolve_type(&self, relocation: &Relocation) -> Option<CatchType> {
+        let _ = relocation;
+        None
+    }
+}
+
+impl<R: SymbolResolver + ?Sized> SymbolResolver for &R {
+    fn resolve_address(&self, address: u32) -> Option<String> {
+        (**self).resolve_address(address)
+    }
+
+    fn resolve_relocation(&self, index: usize, relocation: &Relocation) -> Option<String> {
+        (**self).resolve_relocation(index, relocation)
+    }
+
+    fn resolve_type(&self, relocation: &Relocation) -> Option<CatchType> {
+        (**self).resolve_type(relocation)
+    }
+}
+
+/// Resolver that never finds a name, for rendering with the symbols stored in the table only.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct NoSymbols;
+
+impl SymbolResolver for NoSymbols {
+    fn resolve_address(&self, _address: u32) -> Option<String> {
+        None
+    }
+}
+
+/// A list of names is matched to the dtors of the table by position, like the CLI input
+/// files which list the dtor names in order.
+impl<S: AsRef<str>> SymbolResolver for [S] {
+    fn resolve_address(&self, _address: u32) -> Option<String> {
+        None
+    }
+
+    fn resolve_relocation(&self, index: usize, relocation: &Relocation) -> Option<String> {
+        if let Some(symbol) = &relocation.symbol {
+            return Some(symbol.clone());
+        }
+        match relocation.field {
+            RelocationField::Dtor => self.get(index).map(|name| String::from(name.as_ref())),
+            _ => None,
+        }
+    }
+}
+
+impl<S: AsRef<str>> SymbolResolver for Vec<S> {
+    fn resolve_address(&self, address: u32) -> Option<String> {
+        self.as_slice().resolve_address(address)
+    }
+
+    fn resolve_relocation(&self, index: usize, relocation: &Relocation) -> Option<String> {
+        self.as_slice().resolve_relocation(index, relocation)
+    }
+}
+
+impl<S: AsRef<str>> SymbolResolver for BTreeMap<u32, S> {
+    fn resolve_address(&self, address: u32) -> Option<String> {
+        self.get(&address).map(|name| String::from(name.as_ref()))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<S: AsRef<str>, H: core::hash::BuildHasher> SymbolResolver
+    for std::collections::HashMap<u32, S, H>
+{
+    fn resolve_address(&self, address: u32) -> Option<String> {
+        self.get(&address).map(|name| String::from(name.as_ref()))
+    }
+}
+
+/// Resolver that looks up relocations by their offset in the table instead of the address
+/// they hold, for relocatable tables where the addresses are all zero.
+#[derive(Debug, Clone, Default)]
+pub struct ByOffset<R>(pub R);
+
+impl<R: SymbolResolver> SymbolResolver for ByOffset<R> {
+    fn resolve_address(&self, address: u32) -> Option<String> {
+        self.0.resolve_address(address)
+    }
+
+    fn resolve_relocation(&self, _index: usize, relocation: &Relocation) -> Option<String> {
+        match &relocation.symbol {
+            Some(symbol) => Some(symbol.clone()),
+            None => self.0.resolve_address(relocation.offset),
+        }
+    }
+
+    fn resolve_type(&self, relocation: &Relocation) -> Option<CatchType> {
+        self.0.resolve_type(relocation)
+    }
+}
+
+/// Table of symbols sorted by address.
+#[derive(Debug, Clone, Default)]
+pub struct SymbolTable {
+    symbols: Vec<(u32, String)>,
+}
+
+impl SymbolTable {
+    /// Creates a table from the given address and name pairs. If several symbols share an
+    /// address, the first one is kept.
+    pub fn new(symbols: impl IntoIterator<Item = (u32, String)>) -> Self {
+        let mut symbols: Vec<(u32, String)> = symbols.into_iter().collect();
+        symbols.sort_by_key(|symbol| symbol.0);
+        symbols.dedup_by_key(|symbol| symbol.0);
+        Self { symbols }
+    }
+
+    /// Adds a symbol to the table, replacing the symbol at the same address if there is one.
+    pub fn insert(&mut self, address: u32, name: String) {
+        match self
+            .symbols
+            .binary_search_by_key(&address, |symbol| symbol.0)
+        {
+            Ok(index) => self.symbols[index].1 = name,
+            Err(index) => self.symbols.insert(index, (address, name)),
+        }
+    }
+
+    /// Returns the name of the symbol at the given address, if any.
+    pub fn get(&self, address: u32) -> Option<&str> {
+        self.symbols
+            .binary_search_by_key(&address, |symbol| symbol.0)
+            .ok()
+            .map(|index| self.symbols[index].1.as_str())
+    }
+
+    /// Returns the symbols of the table, sorted by address.
+    pub fn symbols(&self) -> &[(u32, String)] {
+        &self.symbols
+    }
+}
+
+impl SymbolResolver for SymbolTable {
+    fn resolve_address(&self, address: u32) -> Option<String> {
+        self.get(address).map(String::from)
+    }
+}
+
+impl SymbolResolver for MapFile {
+    fn resolve_address(&self, address: u32) -> Option<String> {
+        self.function_name(address).map(String::from)
+    }
+}
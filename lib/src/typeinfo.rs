@@ -0,0 +1,289 @@
+use alloc::string::String;
+use alloc::{format, vec};
+use alloc::vec::Vec;
+
+use crate::demangle::demangle_type;
+use crate::{Dol, Rel, Relocation, SymbolResolver};
+
+/// Maximum length of a type descriptor string.
+const MAX_DESCRIPTOR_LENGTH: usize = 1024;
+
+/// Trait for reading the data that addresses in a table point at, like the type descriptors
+/// of catch blocks.
+pub trait MemoryImage {
+    /// Returns the data from the given address to the end of the section it is in, if the
+    /// address is mapped.
+    fn bytes_at(&self, address: u32) -> Option<&[u8]>;
+
+    /// Returns the data the relocation points at. By default, the address stored in the
+    /// relocation is read.
+    fn relocation_bytes(&self, relocation: &Relocation) -> Option<&[u8]> {
+        self.bytes_at(relocation.address)
+    }
+}
+
+impl<M: MemoryImage + ?Sized> MemoryImage for &M {
+    fn bytes_at(&self, address: u32) -> Option<&[u8]> {
+        (**self).bytes_at(address)
+    }
+
+    fn relocation_bytes(&self, relocation: &Relocation) -> Option<&[u8]> {
+        (**self).relocation_bytes(relocation)
+    }
+}
+
+/// Raw memory dump loaded at the given address, like the main memory of an emulator.
+#[derive(Debug, Copy, Clone)]
+pub struct MemoryDump<'a> {
+    pub address: u32,
+    pub data: &'a [u8],
+}
+
+impl<'a> MemoryDump<'a> {
+    pub fn new(address: u32, data: &'a [u8]) -> Self {
+        Self { address, data }
+    }
+}
+
+impl MemoryImage for MemoryDump<'_> {
+    fn bytes_at(&self, address: u32) -> Option<&[u8]> {
+        let offset = address.checked_sub(self.address)? as usize;
+        self.data.get(offset..)
+    }
+}
+
+impl MemoryImage for Dol<'_> {
+    fn bytes_at(&self, address: u32) -> Option<&[u8]> {
+        self.data_at(address).ok()
+    }
+}
+
+/// Only the module itself can be read, since the other modules are not loaded.
+impl MemoryImage for Rel<'_> {
+    fn bytes_at(&self, _address: u32) -> Option<&[u8]> {
+        None
+    }
+
+    fn relocation_bytes(&self, relocation: &Relocation) -> Option<&[u8]> {
+        let target = relocation.target?;
+        if target.module_id != self.module_id {
+            return None;
+        }
+        self.section_data(target.section as u32)?
+            .get(target.offset as usize..)
+    }
+}
+
+/// Struct for a base class in a class type descriptor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BaseClass {
+    pub name: String,
+    /// Offset of the base class in the derived class
+    pub offset: u32,
+}
+
+/// Enum for the type caught by a catch block or listed in an exception specification.
+///
+/// CodeWarrior stores these as strings. Classes are stored as `!Name!0!Base!offset!...`,
+/// listing the class itself and then each of its base classes with their offsets, or with a
+/// `*` instead of the first `!` for class pointers. Other types use the mangled type, like
+/// `PCc` for `const char*`. A null type pointer catches everything.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CatchType {
+    /// `catch (...)`
+    All,
+    Class {
+        name: String,
+        /// Whether the type is a pointer to the class
+        pointer: bool,
+        bases: Vec<BaseClass>,
+    },
+    /// Any other type, as a readable type
+    Type(String),
+}
+
+impl CatchType {
+    /// Parses a type descriptor string. Returns 'None' if the string is not a valid
+    /// descriptor.
+    pub fn parse(descriptor: &str) -> Option<Self> {
+        if let Some(rest) = descriptor.strip_prefix('!') {
+            return Self::parse_class(rest, false);
+        }
+        if let Some(rest) = descriptor.strip_prefix('*') {
+            return Self::parse_class(rest, true);
+        }
+        demangle_type(descriptor).map(CatchType::Type)
+    }
+
+    fn parse_class(descriptor: &str, pointer: bool) -> Option<Self> {
+        //The string is a list of name and offset pairs, each followed by a '!'
+        let mut parts = descriptor.split('!');
+        let name = parts.next().filter(|name| !name.is_empty())?;
+        let mut bases = vec![];
+        //Skip the offset of the class itself, which is always 0
+        parts.next();
+        loop {
+            let base = match parts.next() {
+                Some(base) if !base.is_empty() => base,
+                _ => break,
+            };
+            let offset = parts.next()?.parse::<u32>().ok()?;
+            bases.push(BaseClass {
+                name: String::from(base),
+                offset,
+            });
+        }
+        Some(CatchType::Class {
+            name: String::from(name),
+            pointer,
+            bases,
+        })
+    }
+
+    /// Returns the readable name of the type, or `...` for catch-all blocks.
+    pub fn type_name(&self) -> String {
+        match self {
+            CatchType::All => String::from("..."),
+            CatchType::Class {
+                name,
+                pointer: true,
+                ..
+            } => format!("{name}*"),
+            CatchType::Class { name, .. } => name.clone(),
+            CatchType::Type(name) => name.clone(),
+        }
+    }
+
+    /// Returns the catch clause for the type, like `catch (const char*)` or `catch (...)`.
+    ///
+    /// The descriptors don't record whether the type is const or caught by reference, since
+    /// the runtime matches thrown types without them, so `catch (const Foo&)` is shown as
+    /// `catch (Foo)`. Qualifiers of pointed-to types are kept, like the `const` in `PCc`.
+    pub fn catch_clause(&self) -> String {
+        format!("catch ({})", self.type_name())
+    }
+}
+
+/// Reads the type descriptor a catch type or spec type relocation points at.
+///
+/// Returns `CatchType::All` for null type pointers, and 'None' if the descriptor can't be
+/// read from the image or is not valid.
+pub fn read_catch_type<M: MemoryImage + ?Sized>(
+    image: &M,
+    relocation: &Relocation,
+) -> Option<CatchType> {
+    if relocation.address == 0 && relocation.symbol.is_none() && relocation.target.is_none() {
+        return Some(CatchType::All);
+    }
+    let bytes = image.relocation_bytes(relocation)?;
+    let length = bytes
+        .iter()
+        .take(MAX_DESCRIPTOR_LENGTH)
+        .position(|&b| b == 0)?;
+    let descriptor = core::str::from_utf8(&bytes[..length]).ok()?;
+    if descriptor.is_empty() || !descriptor.is_ascii() {
+        return None;
+    }
+    CatchType::parse(descriptor)
+}
+
+/// Resolver that reads catch and spec types from a memory image, and looks up the other
+/// symbols with the given resolver.
+#[derive(Debug, Clone)]
+pub struct TypeInfoResolver<M, R> {
+    pub image: M,
+    pub symbols: R,
+}
+
+impl<M: MemoryImage, R: SymbolResolver> TypeInfoResolver<M, R> {
+    pub fn new(image: M, symbols: R) -> Self {
+        Self { image, symbols }
+    }
+}
+
+impl<M: MemoryImage, R: SymbolResolver> SymbolResolver for TypeInfoResolver<M, R> {
+    fn resolve_address(&self, address: u32) -> Option<String> {
+        self.symbols.resolve_address(address)
+    }
+
+    fn resolve_relocation(&self, index: usize, relocation: &Relocation) -> Option<String> {
+        self.symbols.resolve_relocation(index, relocation)
+    }
+
+    fn resolve_type(&self, relocation: &Relocation) -> Option<CatchType> {
+        read_catch_type(&self.image, relocation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RelocationField;
+
+    #[test]
+    fn parse_class() {
+        let catch_type = CatchType::parse("!Foo!0!Base!4!").unwrap();
+        assert_eq!(
+            catch_type,
+            CatchType::Class {
+                name: String::from("Foo"),
+                pointer: false,
+                bases: vec![BaseClass {
+                    name: String::from("Base"),
+                    offset: 4,
+                }],
+            }
+        );
+        assert_eq!(catch_type.catch_clause(), "catch (Foo)");
+    }
+
+    #[test]
+    fn parse_class_pointer() {
+        let catch_type = CatchType::parse("*Foo!0!").unwrap();
+        assert_eq!(
+            catch_type,
+            CatchType::Class {
+                name: String::from("Foo"),
+                pointer: true,
+                bases: vec![],
+            }
+        );
+        assert_eq!(catch_type.catch_clause(), "catch (Foo*)");
+    }
+
+    #[test]
+    fn parse_type() {
+        let catch_type = CatchType::parse("PCc").unwrap();
+        assert_eq!(catch_type, CatchType::Type(String::from("const char*")));
+        assert_eq!(catch_type.catch_clause(), "catch (const char*)");
+        assert_eq!(CatchType::All.catch_clause(), "catch (...)");
+    }
+
+    #[test]
+    fn rejects_invalid_descriptors() {
+        assert_eq!(CatchType::parse("!"), None);
+        assert_eq!(CatchType::parse("!Foo!0!Base!x!"), None);
+        assert_eq!(CatchType::parse("!Foo!0!Base!"), None);
+        assert_eq!(CatchType::parse("Z"), None);
+    }
+
+    #[test]
+    fn read_from_image() {
+        let data = b"\0\0\0\0!Foo!0!\0PCc\0";
+        let image = MemoryDump::new(0x80001000, data);
+        let relocation = |address| Relocation::new(0, address, RelocationField::CatchType);
+        assert_eq!(
+            read_catch_type(&image, &relocation(0x80001004)).map(|t| t.type_name()),
+            Some(String::from("Foo"))
+        );
+        assert_eq!(
+            read_catch_type(&image, &relocation(0x8000100C)).map(|t| t.type_name()),
+            Some(String::from("const char*"))
+        );
+        assert_eq!(
+            read_catch_type(&image, &relocation(0)),
+            Some(CatchType::All)
+        );
+        assert_eq!(read_catch_type(&image, &relocation(0x80001000)), None);
+    }
+}
@@ -0,0 +1,305 @@
+use crate::mem_utils;
+use crate::{
+    DecodeOptions, Endian, ErrorLocation, ExAction, ExActionData, ExceptionAction,
+    ExceptionTableData,
+};
+use crate::{ExtabDecodeError, ExtabFlags, PCAction, Relocation};
+use alloc::vec::Vec;
+
+/// Borrowed view of an exception table.
+///
+/// Unlike `ExceptionTableData`, the view doesn't copy the table data. The header and PC action
+/// list are validated when the view is created, while exception actions are parsed lazily while
+/// iterating over them.
+#[derive(Debug, Clone, Copy)]
+pub struct ExceptionTableView<'a> {
+    data: &'a [u8],
+    options: DecodeOptions,
+    flags: ExtabFlags,
+    actions_start: usize, //Offset of the first action entry, right after the PC action terminator
+}
+
+impl<'a> ExceptionTableView<'a> {
+    /// Creates a view of the provided exception table data.
+    ///
+    /// Returns an error if the header or the PC actions are not valid.
+    pub fn new(data: &'a [u8]) -> Result<Self, ExtabDecodeError> {
+        Self::with_options(data, DecodeOptions::default())
+    }
+
+    /// Creates a view of the provided exception table data with the given byte order and
+    /// target. See `new`.
+    pub fn with_options(data: &'a [u8], options: DecodeOptions) -> Result<Self, ExtabDecodeError> {
+        let endian = options.endian;
+        let length = data.len();
+        if length < 8 {
+            return Err(ExtabDecodeError::ArrayTooSmall {
+                length: length as u32,
+                location: ErrorLocation::new(0, ""),
+            });
+        }
+
+        let mut offset: usize = 0;
+        let flag_val = mem_utils::read_uint16(data, &mut offset, true, "flags", endian)?;
+        let et_field = mem_utils::read_uint16(data, &mut offset, true, "et_field", endian)?;
+
+        let terminator = mem_utils::read_uint32(data, &mut offset, false, "terminator", endian)?;
+        if length == 8 && terminator != 0 {
+            return Err(ExtabDecodeError::InvalidSmallTableTerminator {
+                location: ErrorLocation::new(4, "terminator"),
+            });
+        }
+
+        //Walk over the PC actions to find where the action entries begin
+        let mut index: usize = 0;
+        while read_pc_action(data, &mut offset, endian)
+            .map_err(|e| e.with_pc_action_index(index))?
+            .is_some()
+        {
+            index += 1;
+        }
+
+        Ok(Self {
+            data,
+            options,
+            flags: ExtabFlags::from_raw(flag_val, et_field),
+            actions_start: offset + 4,
+        })
+    }
+
+    /// Returns the table data this view borrows.
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Returns the byte order and target of the table.
+    pub fn options(&self) -> DecodeOptions {
+        self.options
+    }
+
+    /// Returns the header flags of the table.
+    pub fn flags(&self) -> ExtabFlags {
+        self.flags
+    }
+
+    /// Returns an iterator over the PC actions of the table.
+    pub fn pc_actions(&self) -> PCActionIter<'a> {
+        PCActionIter {
+            data: self.data,
+            endian: self.options.endian,
+            offset: 4,
+        }
+    }
+
+    /// Returns an iterator over the exception actions of the table. Each action is parsed
+    /// when it is reached, and iteration stops after the first invalid action. The action data
+    /// is in the byte order from `options`.
+    pub fn exception_actions(&self) -> ExceptionActionIter<'a> {
+        ExceptionActionIter {
+            data: self.data,
+            options: self.options,
+            offset: self.actions_start,
+            index: 0,
+            done: false,
+        }
+    }
+
+    /// Decodes the whole table into an owned `ExceptionTableData`.
+    pub fn to_table_data(&self) -> Result<ExceptionTableData, ExtabDecodeError> {
+        crate::decode_extab_with_options(self.data, self.options)
+    }
+}
+
+/// Iterator over the PC actions of an `ExceptionTableView`.
+#[derive(Debug, Clone)]
+pub struct PCActionIter<'a> {
+    data: &'a [u8],
+    endian: Endian,
+    offset: usize,
+}
+
+impl Iterator for PCActionIter<'_> {
+    type Item = PCAction;
+
+    fn next(&mut self) -> Option<PCAction> {
+        //The PC actions were already validated when the view was created
+        read_pc_action(self.data, &mut self.offset, self.endian)
+            .ok()
+            .flatten()
+    }
+}
+
+/// Iterator over the exception actions of an `ExceptionTableView`.
+#[derive(Debug, Clone)]
+pub struct ExceptionActionIter<'a> {
+    data: &'a [u8],
+    options: DecodeOptions,
+    offset: usize,
+    index: usize,
+    done: bool,
+}
+
+impl<'a> Iterator for ExceptionActionIter<'a> {
+    type Item = Result<ExceptionActionRef<'a>, ExtabDecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.offset >= self.data.len() {
+            return None;
+        }
+        let result = ExceptionActionRef::read(self.data, &mut self.offset, self.options)
+            .map_err(|e| e.with_action_index(self.index));
+        self.index += 1;
+        self.done = result.is_err();
+        Some(result)
+    }
+}
+
+/// Borrowed version of `ExceptionAction`, with the action data pointing into the table.
+#[derive(Debug, Clone, Copy)]
+pub struct ExceptionActionRef<'a> {
+    pub action_offset: u32,
+    pub action_type: ExAction,
+    pub action_param: u8,
+    pub has_end_bit: bool,
+    pub bytes: &'a [u8],
+}
+
+impl<'a> ExceptionActionRef<'a> {
+    /// Parses the action entry at the given offset of the table data, and moves the offset
+    /// to the next entry.
+    pub(crate) fn read(
+        data: &'a [u8],
+        offset: &mut usize,
+        options: DecodeOptions,
+    ) -> Result<Self, ExtabDecodeError> {
+        let action_offset = *offset as u32;
+        let action_type_byte = mem_utils::read_byte(data, offset, true, "action_type")?;
+        let has_end_bit = (action_type_byte & 0x80) != 0;
+        let action_type_value: u32 = (action_type_byte & 0x7F) as u32;
+        let action_type = match ExAction::from_int(action_type_value as i32) {
+            Some(action) => action,
+            None => {
+                return Err(ExtabDecodeError::InvalidActionValue {
+                    value: action_type_value,
+                    location: ErrorLocation::new(action_offset, "action_type"),
+                })
+            }
+        };
+        let action_param = mem_utils::read_byte(data, offset, true, "action_param")?;
+
+        //Since the way action data is stored is too varied, we just keep the remaining data as a
+        //byte array to be decoded later.
+        let spec_count = match action_type {
+            ExAction::Specification => {
+                mem_utils::read_uint16(data, offset, false, "specs", options.endian)?
+            }
+            _ => 0,
+        };
+        let size = action_type.data_size(spec_count);
+
+        let start_index = *offset;
+        let bytes = match mem_utils::read_bytes(data, offset, size, true, "action_data") {
+            Ok(bytes) => bytes,
+            Err(error) => {
+                //Decode the truncated data to find out which field is missing
+                let truncated = &data[start_index..];
+                return Err(action_type
+                    .decode_data(truncated, action_offset, options)
+                    .err()
+                    .unwrap_or(error));
+            }
+        };
+
+        Ok(Self {
+            action_offset,
+            action_type,
+            action_param,
+            has_end_bit,
+            bytes,
+        })
+    }
+
+    /// Returns whether this action has a destuctor reference or not.
+    pub fn has_dtor_ref(&self) -> bool {
+        self.action_type.has_dtor_ref()
+    }
+
+    /// Returns the relocation data for the dtor function in this action entry, if any.
+    pub fn get_dtor_relocation(&self) -> Option<(u32, u32)> {
+        self.get_dtor_relocation_with_options(DecodeOptions::default())
+    }
+
+    /// Returns the relocation data for the dtor function, reading the action data with the
+    /// byte order of the table. See `get_dtor_relocation`.
+    pub fn get_dtor_relocation_with_options(&self, options: DecodeOptions) -> Option<(u32, u32)> {
+        self.action_type.get_dtor_relocation(self.bytes, options)
+    }
+
+    /// Returns the relocations for the dtor and type info addresses in this action entry.
+    pub fn get_relocations(&self) -> Result<Vec<Relocation>, ExtabDecodeError> {
+        self.get_relocations_with_options(DecodeOptions::default())
+    }
+
+    /// Returns the relocations for the dtor and type info addresses, reading the action data
+    /// with the byte order of the table. See `get_relocations`.
+    pub fn get_relocations_with_options(
+        &self,
+        options: DecodeOptions,
+    ) -> Result<Vec<Relocation>, ExtabDecodeError> {
+        self.action_type
+            .get_relocations(self.bytes, self.action_offset, options)
+    }
+
+    /// Decodes the action data from the borrowed byte array. See
+    /// `ExceptionAction::get_exaction_data`.
+    pub fn get_exaction_data(&self) -> Result<ExActionData, ExtabDecodeError> {
+        self.get_exaction_data_with_options(DecodeOptions::default())
+    }
+
+    /// Decodes the action data with the byte order of the table. See `get_exaction_data`.
+    pub fn get_exaction_data_with_options(
+        &self,
+        options: DecodeOptions,
+    ) -> Result<ExActionData, ExtabDecodeError> {
+        self.action_type
+            .decode_data(self.bytes, self.action_offset, options)
+    }
+}
+
+impl From<ExceptionActionRef<'_>> for ExceptionAction {
+    fn from(action: ExceptionActionRef<'_>) -> Self {
+        Self {
+            action_offset: action.action_offset,
+            action_type: action.action_type,
+            action_param: action.action_param,
+            has_end_bit: action.has_end_bit,
+            bytes: action.bytes.into(),
+        }
+    }
+}
+
+/// Parses the PC action entry at the given offset of the table data. Returns 'None' if the
+/// terminator was reached, in which case the offset is left pointing at it.
+pub(crate) fn read_pc_action(
+    data: &[u8],
+    offset: &mut usize,
+    endian: Endian,
+) -> Result<Option<PCAction>, ExtabDecodeError> {
+    let value =
+        mem_utils::read_uint32(data, offset, false, "terminator", endian).map_err(|_| {
+            ExtabDecodeError::MissingPcTerminator {
+                location: ErrorLocation::new(*offset as u32, "terminator"),
+            }
+        })?;
+    if value == 0 {
+        return Ok(None);
+    }
+    let mut pcaction = PCAction::new();
+    pcaction.start_pc = mem_utils::read_uint32(data, offset, true, "start_pc", endian)?;
+    let range_size: u32 =
+        (mem_utils::read_uint16(data, offset, true, "range_size", endian)? as u32) * 4; //range size is encoded as size >> 2
+    pcaction.end_pc = pcaction.start_pc.wrapping_add(range_size);
+    pcaction.action_offset =
+        mem_utils::read_uint16(data, offset, true, "action_offset", endian)? as u32;
+    Ok(Some(pcaction))
+}